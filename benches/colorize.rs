@@ -0,0 +1,54 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lolcrab::Lolcrab;
+
+const ASCII: &str = "\
+Vestibulum ante ipsum primis in faucibus orci luctus et
+ultrices posuere cubilia curae; Pellentesque at tellus
+vitae massa hendrerit hendrerit. Mauris laoreet lectus
+";
+
+const CJK: &str = "\
+色は匂へど散りぬるを我が世誰ぞ常ならむ有為の奥山今日越えて
+浅き夢見じ酔ひもせず天地玄黄宇宙洪荒日月盈昃辰宿列張寒來暑往
+동해 물과 백두산이 마르고 닳도록 하느님이 보우하사 우리나라 만세
+";
+
+fn new_lol() -> Lolcrab {
+    Lolcrab::new(None, Some(Box::new(noise::OpenSimplex::new(0))))
+}
+
+fn colorize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("colorize");
+
+    for (name, text) in [("ascii", ASCII), ("cjk", CJK)] {
+        let text = text.repeat(2000);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+
+        for (mode, linear, invert) in [
+            ("noise", false, false),
+            ("linear", true, false),
+            ("invert", false, true),
+        ] {
+            for lut_size in [0, 1024] {
+                let id = BenchmarkId::new(format!("{name}/{mode}"), format!("lut-{lut_size}"));
+                group.bench_with_input(id, &text, |b, text| {
+                    let mut lol = new_lol();
+                    lol.set_linear(linear);
+                    lol.set_invert(invert);
+                    lol.set_lut_size(lut_size);
+                    let mut out = Vec::with_capacity(text.len() * 25);
+                    b.iter(|| {
+                        out.clear();
+                        lol.reset_position();
+                        lol.colorize_read(&mut text.as_bytes(), &mut out).unwrap();
+                    });
+                });
+            }
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, colorize);
+criterion_main!(benches);
@@ -3,7 +3,7 @@ include!("src/cli.rs");
 
 #[cfg(feature = "cli")]
 fn main() -> Result<(), clap::Error> {
-    use clap::CommandFactory;
+    use clap::{CommandFactory, ValueEnum};
     use clap_complete::{generate_to, Shell};
     use std::{fs, path, process::exit};
 
@@ -2,6 +2,7 @@
 
 use lolcrab::Lolcrab;
 use std::io::{self, BufReader};
+use std::sync::Arc;
 
 const TEXT: &str = "\
 Vestibulum ante ipsum primis in faucibus orci luctus et
@@ -37,7 +38,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     println!("\n--- randomize_position()\n");
-    lol.gradient = Box::new(colorgrad::preset::viridis());
+    lol.gradient = Arc::new(colorgrad::preset::viridis());
     lol.set_invert(true);
     for i in 0..7 {
         lol.randomize_position();
@@ -45,7 +46,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     println!("\n--- animate\n");
-    lol.gradient = Box::new(colorgrad::preset::sinebow());
+    lol.gradient = Arc::new(colorgrad::preset::sinebow());
     lol.set_invert(false);
     lol.colorize_read_anim(&mut BufReader::new(TEXT.as_bytes()), &mut stdout)?;
     Ok(())
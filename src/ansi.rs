@@ -0,0 +1,79 @@
+use crate::lut::Rgb;
+
+/// SGR escape sequence, formatted without allocation
+pub(crate) struct Sgr {
+    buf: [u8; 40],
+    len: usize,
+}
+
+impl Sgr {
+    /// Sets the foreground color
+    pub(crate) fn fg(fg: Rgb) -> Self {
+        let mut sgr = Self::start();
+        sgr.push(b"38;2;");
+        sgr.push_rgb(fg);
+        sgr.push(b"m");
+        sgr
+    }
+
+    /// Sets the background and foreground colors
+    pub(crate) fn bg_fg(bg: Rgb, fg: Rgb) -> Self {
+        let mut sgr = Self::start();
+        sgr.push(b"48;2;");
+        sgr.push_rgb(bg);
+        sgr.push(b";38;2;");
+        sgr.push_rgb(fg);
+        sgr.push(b"m");
+        sgr
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    fn start() -> Self {
+        let mut sgr = Self {
+            buf: [0; 40],
+            len: 0,
+        };
+        sgr.push(b"\x1B[");
+        sgr
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn push_rgb(&mut self, [r, g, b]: Rgb) {
+        self.push_u8(r);
+        self.push(b";");
+        self.push_u8(g);
+        self.push(b";");
+        self.push_u8(b);
+    }
+
+    fn push_u8(&mut self, n: u8) {
+        if n >= 100 {
+            self.push(&[b'0' + n / 100, b'0' + n / 10 % 10, b'0' + n % 10]);
+        } else if n >= 10 {
+            self.push(&[b'0' + n / 10, b'0' + n % 10]);
+        } else {
+            self.push(&[b'0' + n]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sgr;
+
+    #[test]
+    fn format() {
+        assert_eq!(Sgr::fg([0, 9, 10]).as_bytes(), b"\x1B[38;2;0;9;10m");
+        assert_eq!(
+            Sgr::bg_fg([99, 100, 255], [255, 255, 255]).as_bytes(),
+            b"\x1B[48;2;99;100;255;38;2;255;255;255m"
+        );
+    }
+}
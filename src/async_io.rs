@@ -0,0 +1,158 @@
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::time;
+
+use crate::stream::Stream;
+use crate::terminal;
+use crate::Lolcrab;
+
+impl Lolcrab {
+    async fn colorize_anim_async(
+        &mut self,
+        text: &[u8],
+        out: &mut (impl AsyncWrite + Unpin),
+    ) -> std::io::Result<()> {
+        let mut frame = Vec::new();
+        self.x = -(self.anim_duration as isize - 1) * self.text_len(text);
+        for _ in 0..self.anim_duration {
+            frame.clear();
+            frame.extend_from_slice(b"\x1B[0G");
+            self.colorize(text, &mut frame)?;
+            out.write_all(&frame).await?;
+            out.flush().await?;
+            time::sleep(self.anim_sleep).await;
+        }
+        out.write_all(b"\n").await?;
+        self.end_line();
+        out.flush().await
+    }
+
+    /// Async version of [`Lolcrab::colorize`]
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `out` causes I/O erros
+    pub async fn colorize_async(
+        &mut self,
+        text: &[u8],
+        out: &mut (impl AsyncWrite + Unpin),
+    ) -> std::io::Result<()> {
+        let mut buf = Vec::with_capacity(text.len() * 20);
+        self.colorize(text, &mut buf)?;
+        out.write_all(&buf).await?;
+        out.flush().await
+    }
+
+    /// Async version of [`Lolcrab::colorize_read_anim`]. Sleeps between frames
+    /// using `tokio::time`, so it does not block the runtime.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `input` or `out` cause I/O errors
+    pub async fn colorize_read_anim_async(
+        &mut self,
+        input: &mut (impl AsyncBufRead + Unpin),
+        out: &mut (impl AsyncWrite + Unpin),
+    ) -> std::io::Result<()> {
+        out.write_all(terminal::HIDE_CURSOR).await?;
+
+        let mut line = Vec::new();
+        let result = async {
+            while input.read_until(b'\n', &mut line).await? != 0 {
+                self.colorize_anim_async(trim_line(&line), out).await?;
+                line.clear();
+            }
+            Ok(())
+        }
+        .await;
+
+        // shows the cursor again, also on errors
+        out.write_all(terminal::RESTORE).await?;
+        out.flush().await?;
+        result
+    }
+
+    /// Async version of [`Lolcrab::colorize_read`], also colorizes the input as it arrives
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `input` or `out` cause I/O errors
+    pub async fn colorize_read_async(
+        &mut self,
+        input: &mut (impl AsyncBufRead + Unpin),
+        out: &mut (impl AsyncWrite + Unpin),
+    ) -> std::io::Result<()> {
+        let mut stream = Stream::default();
+        let mut buf = Vec::new();
+        loop {
+            let chunk = input.fill_buf().await?;
+            if chunk.is_empty() {
+                break;
+            }
+            buf.clear();
+            stream.feed(self, chunk, &mut buf)?;
+            let n = chunk.len();
+            input.consume(n);
+            out.write_all(&buf).await?;
+            out.flush().await?;
+        }
+        buf.clear();
+        stream.finish(self, &mut buf)?;
+        out.write_all(&buf).await?;
+        out.flush().await
+    }
+}
+
+// Strip the line terminator, the same way `bstr`'s `for_byte_line` does
+fn trim_line(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::new_lol;
+    use crate::Lolcrab;
+
+    // short animations
+    fn anim_lol(seed: u32) -> Lolcrab {
+        let mut lol = new_lol(seed);
+        lol.set_anim_speed(30);
+        lol.set_anim_duration(2);
+        lol
+    }
+
+    #[tokio::test]
+    async fn eq_sync_async() {
+        let text = "Lolcrab\n\tis the best\r\n🦀";
+
+        let mut lol1 = anim_lol(3);
+        let mut out1 = Vec::new();
+        lol1.colorize_read(&mut text.as_bytes(), &mut out1).unwrap();
+
+        let mut lol2 = anim_lol(3);
+        let mut out2 = Vec::new();
+        lol2.colorize_read_async(&mut text.as_bytes(), &mut out2)
+            .await
+            .unwrap();
+
+        assert_eq!(out1, out2);
+    }
+
+    #[tokio::test]
+    async fn eq_sync_async_anim() {
+        let text = "Lolcrab\r\nRust\n";
+
+        let mut lol1 = anim_lol(5);
+        let mut out1 = Vec::new();
+        lol1.colorize_read_anim(&mut text.as_bytes(), &mut out1)
+            .unwrap();
+
+        let mut lol2 = anim_lol(5);
+        let mut out2 = Vec::new();
+        lol2.colorize_read_anim_async(&mut text.as_bytes(), &mut out2)
+            .await
+            .unwrap();
+
+        assert_eq!(out1, out2);
+    }
+}
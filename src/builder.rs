@@ -0,0 +1,361 @@
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+use std::time;
+
+use crate::{Continuity, Lolcrab};
+
+/// Error returned by [`LolcrabBuilder::build`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// The value is outside of the valid range, or not a number
+    OutOfRange {
+        param: &'static str,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange {
+                param,
+                value,
+                min,
+                max,
+            } => write!(f, "invalid {param} {value}, expected {min}..={max}"),
+        }
+    }
+}
+
+impl error::Error for BuildError {}
+
+/// Builds [`Settings`], validating every parameter.
+///
+/// Unlike the setters of [`Lolcrab`], values out of range are errors
+/// instead of being clamped.
+///
+/// # Example
+///
+/// ```
+/// # use std::error::Error;
+/// use lolcrab::LolcrabBuilder;
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let settings = LolcrabBuilder::new()
+///     .gradient(colorgrad::preset::viridis())
+///     .seed(7)
+///     .linear(true)
+///     .spread(30.0)
+///     .build()?;
+///
+/// let mut lol = settings.lolcrab();
+/// lol.colorize_str("Lolcrab is the best", &mut std::io::stdout())?;
+///
+/// assert!(LolcrabBuilder::new().noise_scale(0.5).build().is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+#[must_use]
+pub struct LolcrabBuilder {
+    gradient: Option<Arc<dyn colorgrad::Gradient + Send + Sync>>,
+    noise: Option<Arc<dyn noise::NoiseFn<f64, 2> + Send + Sync>>,
+    seed: Option<u64>,
+    noise_scale: f64,
+    invert: bool,
+    tab_width: usize,
+    anim_speed: u64,
+    anim_duration: usize,
+    linear: bool,
+    angle: Option<f32>,
+    spread: f32,
+    offset: Option<f32>,
+    lut_size: usize,
+    compress: bool,
+    tolerance: f32,
+    paragraph: Continuity,
+}
+
+impl Default for LolcrabBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LolcrabBuilder {
+    pub fn new() -> Self {
+        Self {
+            gradient: None,
+            noise: None,
+            seed: None,
+            noise_scale: 0.034,
+            invert: false,
+            tab_width: 4,
+            anim_speed: 150,
+            anim_duration: 5,
+            linear: false,
+            angle: None,
+            spread: 13.0,
+            offset: None,
+            lut_size: 1024,
+            compress: false,
+            tolerance: 0.0,
+            paragraph: Continuity::Continue,
+        }
+    }
+
+    /// Color gradient (default: rainbow)
+    pub fn gradient(mut self, gradient: impl colorgrad::Gradient + Send + Sync + 'static) -> Self {
+        self.gradient = Some(Arc::new(gradient));
+        self
+    }
+
+    /// Like [`LolcrabBuilder::gradient`], for a gradient that is already boxed
+    pub fn boxed_gradient(mut self, gradient: Box<dyn colorgrad::Gradient + Send + Sync>) -> Self {
+        self.gradient = Some(gradient.into());
+        self
+    }
+
+    /// Noise function (default: `OpenSimplex` with a random seed)
+    pub fn noise(mut self, noise: impl noise::NoiseFn<f64, 2> + Send + Sync + 'static) -> Self {
+        self.noise = Some(Arc::new(noise));
+        self
+    }
+
+    /// Seed for the noise, for the angle and offset if they are not set,
+    /// and for [`Lolcrab::randomize_position`]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Noise scale (0.001..=0.25)
+    pub fn noise_scale(mut self, scale: f64) -> Self {
+        self.noise_scale = scale;
+        self
+    }
+
+    /// Colorize the background
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Tab stop width (1..=64)
+    pub fn tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width;
+        self
+    }
+
+    /// Milliseconds between animation frames (30..=200)
+    pub fn anim_speed(mut self, speed: u64) -> Self {
+        self.anim_speed = speed;
+        self
+    }
+
+    /// Number of animation frames (1..=30)
+    pub fn anim_duration(mut self, duration: usize) -> Self {
+        self.anim_duration = duration;
+        self
+    }
+
+    /// Linear mode
+    pub fn linear(mut self, linear: bool) -> Self {
+        self.linear = linear;
+        self
+    }
+
+    /// Angle in degrees (0..=360)
+    pub fn angle(mut self, angle: f32) -> Self {
+        self.angle = Some(angle);
+        self
+    }
+
+    /// Spread (0..=100)
+    pub fn spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// Color gradient offset (0..=1)
+    pub fn offset(mut self, offset: f32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Number of precomputed gradient colors, 0 to disable
+    pub fn lut_size(mut self, size: usize) -> Self {
+        self.lut_size = size;
+        self
+    }
+
+    /// Only write colors that differ from the previous one
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Color tolerance for compression, in Oklab ΔE (0..=1)
+    pub fn color_tolerance(mut self, delta_e: f32) -> Self {
+        self.tolerance = delta_e;
+        self
+    }
+
+    /// Position at the start of a paragraph (default: continue)
+    pub fn paragraph_continuity(mut self, continuity: Continuity) -> Self {
+        self.paragraph = continuity;
+        self
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if a parameter is out of range
+    pub fn build(self) -> Result<Settings, BuildError> {
+        check("noise scale", self.noise_scale, 0.001, 0.25)?;
+        check("tab width", self.tab_width as f64, 1.0, 64.0)?;
+        check("animation speed", self.anim_speed as f64, 30.0, 200.0)?;
+        check("animation duration", self.anim_duration as f64, 1.0, 30.0)?;
+        check("spread", self.spread.into(), 0.0, 100.0)?;
+        check("color tolerance", self.tolerance.into(), 0.0, 1.0)?;
+        if let Some(angle) = self.angle {
+            check("angle", angle.into(), 0.0, 360.0)?;
+        }
+        if let Some(offset) = self.offset {
+            check("offset", offset.into(), 0.0, 1.0)?;
+        }
+
+        // the angle, offset and noise are random if not set
+        let mut lol = Lolcrab::init(None, None, self.seed);
+        if let Some(gradient) = self.gradient {
+            lol.gradient = gradient;
+        }
+        if let Some(noise) = self.noise {
+            lol.noise = noise;
+        }
+        lol.noise_scale = self.noise_scale;
+        lol.invert = self.invert;
+        lol.tab_width = self.tab_width as isize;
+        lol.anim_sleep = time::Duration::from_millis(self.anim_speed);
+        lol.anim_duration = self.anim_duration;
+        lol.linear = self.linear;
+        if let Some(angle) = self.angle {
+            lol.angle = angle;
+        }
+        lol.set_spread(self.spread);
+        if let Some(offset) = self.offset {
+            lol.offset = offset;
+        }
+        lol.lut_size = self.lut_size;
+        lol.compress = self.compress;
+        lol.tolerance = self.tolerance;
+        lol.paragraph = self.paragraph;
+
+        Ok(Settings { lol })
+    }
+}
+
+pub(crate) fn check(param: &'static str, value: f64, min: f64, max: f64) -> Result<(), BuildError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(BuildError::OutOfRange {
+            param,
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+/// Validated, immutable configuration, created by [`LolcrabBuilder`].
+///
+/// Every [`Lolcrab`] created from the same `Settings` produces the same output.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub(crate) lol: Lolcrab,
+}
+
+impl Settings {
+    /// Create a new `Lolcrab` at the start position
+    #[must_use]
+    pub fn lolcrab(&self) -> Lolcrab {
+        self.lol.clone()
+    }
+}
+
+impl From<&Settings> for Lolcrab {
+    fn from(settings: &Settings) -> Self {
+        settings.lolcrab()
+    }
+}
+
+impl Lolcrab {
+    /// Returns a [`LolcrabBuilder`]
+    pub fn builder() -> LolcrabBuilder {
+        LolcrabBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BuildError, LolcrabBuilder};
+
+    #[test]
+    fn out_of_range() {
+        let err = LolcrabBuilder::new().noise_scale(0.3).build().unwrap_err();
+        assert_eq!(
+            err,
+            BuildError::OutOfRange {
+                param: "noise scale",
+                value: 0.3,
+                min: 0.001,
+                max: 0.25
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "invalid noise scale 0.3, expected 0.001..=0.25"
+        );
+
+        assert!(LolcrabBuilder::new().tab_width(0).build().is_err());
+        assert!(LolcrabBuilder::new().anim_speed(10).build().is_err());
+        assert!(LolcrabBuilder::new().anim_duration(31).build().is_err());
+        assert!(LolcrabBuilder::new().angle(-1.0).build().is_err());
+        assert!(LolcrabBuilder::new().spread(f32::NAN).build().is_err());
+        assert!(LolcrabBuilder::new().offset(1.5).build().is_err());
+        assert!(LolcrabBuilder::new().color_tolerance(2.0).build().is_err());
+        assert!(LolcrabBuilder::new()
+            .angle(360.0)
+            .offset(0.0)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn same_output() {
+        let text = "Lolcrab\nis the best";
+        for linear in [false, true] {
+            let settings = LolcrabBuilder::new()
+                .seed(3)
+                .linear(linear)
+                .build()
+                .unwrap();
+
+            let mut out1 = Vec::new();
+            settings.lolcrab().colorize_str(text, &mut out1).unwrap();
+
+            let mut out2 = Vec::new();
+            let settings = LolcrabBuilder::new()
+                .seed(3)
+                .linear(linear)
+                .build()
+                .unwrap();
+            settings.lolcrab().colorize_str(text, &mut out2).unwrap();
+
+            assert_eq!(out1, out2);
+        }
+    }
+}
@@ -1,83 +1,12 @@
-use std::f32::consts::{FRAC_PI_3, PI};
 use std::path;
 
-use clap::{Parser, ValueEnum};
-use colorgrad::Color;
-
-const PI2_3: f32 = PI * 2.0 / 3.0;
-
-#[derive(Clone)]
-struct LolcatGradient {}
-
-impl colorgrad::Gradient for LolcatGradient {
-    fn at(&self, t: f32) -> Color {
-        let t = (0.5 - t) * PI;
-        Color::new(
-            (t + FRAC_PI_3).sin().powi(2).clamp(0.0, 1.0),
-            t.sin().powi(2).clamp(0.0, 1.0),
-            (t + PI2_3).sin().powi(2).clamp(0.0, 1.0),
-            1.0,
-        )
-    }
-}
-
-#[derive(Debug, Clone, ValueEnum)]
-pub enum Gradient {
-    Cividis,
-    Cool,
-    Cubehelix,
-    Fruits,
-    Inferno,
-    Lolcat,
-    Magma,
-    Plasma,
-    Rainbow,
-    RdYlGn,
-    Sinebow,
-    Spectral,
-    Turbo,
-    Viridis,
-    Warm,
-}
-
-impl Gradient {
-    pub fn to_gradient(&self) -> Box<dyn colorgrad::Gradient> {
-        match self {
-            Gradient::Cividis => Box::new(colorgrad::preset::cividis()),
-            Gradient::Cool => Box::new(colorgrad::preset::cool()),
-            Gradient::Cubehelix => Box::new(colorgrad::preset::cubehelix_default()),
-            Gradient::Inferno => Box::new(colorgrad::preset::inferno()),
-            Gradient::Lolcat => Box::new(LolcatGradient {}),
-            Gradient::Magma => Box::new(colorgrad::preset::magma()),
-            Gradient::Plasma => Box::new(colorgrad::preset::plasma()),
-            Gradient::Rainbow => Box::new(colorgrad::preset::rainbow()),
-            Gradient::RdYlGn => Box::new(colorgrad::preset::rd_yl_gn()),
-            Gradient::Sinebow => Box::new(colorgrad::preset::sinebow()),
-            Gradient::Spectral => Box::new(colorgrad::preset::spectral()),
-            Gradient::Turbo => Box::new(colorgrad::preset::turbo()),
-            Gradient::Viridis => Box::new(colorgrad::preset::viridis()),
-            Gradient::Warm => Box::new(colorgrad::preset::warm()),
-            Gradient::Fruits => build_gradient(&[
-                "#00c21c", "#009dc9", "#ffd43e", "#ff2a70", "#b971ff", "#7ce300", "#feff62",
-            ]),
-        }
-    }
-}
-
-fn build_gradient(colors: &[&str]) -> Box<dyn colorgrad::Gradient> {
-    Box::new(
-        colorgrad::GradientBuilder::new()
-            .html_colors(colors)
-            .mode(colorgrad::BlendMode::Oklab)
-            .build::<colorgrad::CatmullRomGradient>()
-            .unwrap(),
-    )
-}
+use clap::Parser;
 
 #[derive(Clone, Debug, Parser)]
 #[command(
     name = "lolcrab",
     version,
+    args_override_self = true,
     disable_help_flag = true,
     disable_version_flag = true
 )]
@@ -86,18 +15,17 @@ pub struct Opt {
     #[arg(name = "File", default_value = "-", value_parser = clap::value_parser!(path::PathBuf))]
     pub files: Vec<path::PathBuf>,
 
-    /// Set color gradient
+    /// Set color gradient, a preset or a gradient from the config file
     #[arg(
         short,
         long,
-        value_enum,
         default_value = "rainbow",
         value_name = "NAME",
-        hide_possible_values = true
+        value_parser = GradientName
     )]
-    pub gradient: Gradient,
+    pub gradient: String,
 
-    /// Show all preset gradients
+    /// Show all preset gradients and the gradients from the config file
     #[arg(long)]
     pub presets: bool,
 
@@ -105,10 +33,34 @@ pub struct Opt {
     #[arg(short = 'c', long, value_name = "CSS Gradient")]
     pub custom: Option<String>,
 
+    /// Read the gradient from a GIMP (.ggr), JSON, TOML or CSS file
+    #[arg(long, value_name = "PATH")]
+    pub gradient_file: Option<path::PathBuf>,
+
+    /// Reverse the gradient
+    #[arg(short = 'R', long)]
+    pub reverse: bool,
+
+    /// Use only a part of the gradient, e.g. 0.2..0.8
+    #[arg(long, value_name = "START..END", value_parser = parse_range)]
+    pub range: Option<(f32, f32)>,
+
     /// Sharp gradient
     #[arg(long, value_name = "NUM")]
     pub sharp: Option<u8>,
 
+    /// Smoothness of the sharp gradient (0..1) [default: 0.15]
+    #[arg(long, value_name = "FLOAT")]
+    pub smoothness: Option<f32>,
+
+    /// Blend mode of custom and random gradients [default: oklab]
+    #[arg(long, value_name = "MODE", value_parser = ["rgb", "linear-rgb", "oklab"])]
+    pub blend_mode: Option<String>,
+
+    /// Interpolation of custom and random gradients [default: catmull-rom]
+    #[arg(long, value_name = "NAME", value_parser = ["linear", "basis", "catmull-rom"])]
+    pub interpolation: Option<String>,
+
     /// Noise scale (0.01..0.1)
     #[arg(short, long, default_value = "0.034", value_name = "FLOAT")]
     pub scale: f64,
@@ -121,10 +73,36 @@ pub struct Opt {
     #[arg(short = 'i', long)]
     pub invert: bool,
 
+    /// Only write colors that differ from the previous one
+    #[arg(short = 'z', long)]
+    pub compress: bool,
+
+    /// Skip colors closer than this to the previous one (Oklab ΔE) [default: 0]
+    #[arg(long, value_name = "FLOAT", requires = "compress")]
+    pub tolerance: Option<f32>,
+
     /// Use random colors as custom gradient (1..15)
     #[arg(short = 'r', long, value_name = "NUM", value_parser = clap::value_parser!(u8).range(1..=15))]
     pub random_colors: Option<u8>,
 
+    /// How the random colors are chosen [default: any]
+    #[arg(long, value_name = "SCHEME", requires = "random_colors", value_parser = [
+        "any", "analogous", "complementary", "triadic", "tetradic", "pastel", "neon", "monochrome",
+    ])]
+    pub random_scheme: Option<String>,
+
+    /// Print the random colors as a --custom gradient to stderr
+    #[arg(long, requires = "random_colors")]
+    pub print_colors: bool,
+
+    /// Gradient position at the start of every file after the first [default: continue]
+    #[arg(long, value_name = "MODE", value_parser = ["continue", "reset", "randomize"])]
+    pub per_file: Option<String>,
+
+    /// Gradient position after blank lines [default: continue]
+    #[arg(long, value_name = "MODE", value_parser = ["continue", "reset", "randomize"])]
+    pub per_paragraph: Option<String>,
+
     /// Lolcat mode (equal to: --linear --gradient lolcat)
     #[arg(short = 'L', long)]
     pub lolcat: bool,
@@ -141,12 +119,85 @@ pub struct Opt {
     #[arg(long)]
     pub speed: Option<u8>,
 
+    /// One color for every column, split as: csv, tsv, whitespace or regex (see --separator)
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        conflicts_with = "animate",
+        value_parser = ["csv", "tsv", "whitespace", "regex"]
+    )]
+    pub columns: Option<String>,
+
+    /// Regular expression matching the column separators of --columns regex
+    #[arg(
+        long,
+        value_name = "REGEX",
+        requires = "columns",
+        required_if_eq("columns", "regex")
+    )]
+    pub separator: Option<String>,
+
+    /// Colorize using multiple threads (ignored in animation mode)
+    #[cfg(feature = "rayon")]
+    #[arg(long)]
+    pub parallel: bool,
+
+    /// Render the text in large letters with a FIGlet font, reads the whole input first
+    #[arg(long)]
+    pub banner: bool,
+
+    /// Font of the banner, a .flf file or one of: standard, small [default: standard]
+    #[arg(long, value_name = "NAME|PATH", requires = "banner")]
+    pub font: Option<String>,
+
+    /// Draw a border around the text, reads the whole input first [default: single]
+    #[arg(
+        long = "box",
+        value_name = "STYLE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "single",
+        value_parser = ["single", "double", "rounded", "heavy", "ascii"]
+    )]
+    pub frame: Option<String>,
+
+    /// Empty columns and lines between the box and the text [default: 0]
+    #[arg(long, value_name = "NUM", requires = "frame")]
+    pub padding: Option<usize>,
+
+    /// Title in the top border of the box
+    #[arg(long, value_name = "TEXT", requires = "frame")]
+    pub title: Option<String>,
+
     /// Activate linear mode
     #[arg(short = 'l', long, help_heading = Some("Linear Mode"))]
     pub linear: bool,
 
+    /// Fit one pass of the gradient to the text of each file, reads the whole input first
+    #[arg(long, conflicts_with = "animate", help_heading = Some("Linear Mode"))]
+    pub fit: bool,
+
+    /// Stretch one pass of the gradient across every line, as wide as the terminal or COLUMNS,
+    /// the angle is always 0
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "0",
+        conflicts_with = "fit",
+        help_heading = Some("Linear Mode")
+    )]
+    pub stretch: Option<usize>,
+
     /// Angle in degrees (0..360) [default: random]
-    #[arg(short = 'A', long, value_name = "ANGLE", help_heading = Some("Linear Mode"))]
+    #[arg(
+        short = 'A',
+        long,
+        value_name = "ANGLE",
+        allow_negative_numbers = true,
+        help_heading = Some("Linear Mode")
+    )]
     pub angle: Option<f32>,
 
     /// Gradient spread (0..100) [default: 13]
@@ -157,6 +208,14 @@ pub struct Opt {
     #[arg(long, help_heading = Some("Linear Mode"))]
     pub offset: Option<f32>,
 
+    /// Use the options of a profile from the config file
+    #[arg(long, value_name = "NAME", env = "LOLCRAB_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Print the options in use as a config file to stderr, seed and random values included
+    #[arg(long)]
+    pub dump_config: bool,
+
     /// Print config file location
     #[arg(long)]
     pub config_file: bool,
@@ -170,8 +229,69 @@ pub struct Opt {
     pub version: bool,
 }
 
+// Only the names are needed here
+macro_rules! presets {
+    ($($variant:ident $name:literal => $gradient:expr,)*) => {
+        const PRESETS: &[&str] = &[$($name),*];
+    };
+}
+
+include!("presets.rs");
+
+// Any name, the gradients of the config file are only known later, but the
+// presets are shown by the help and the shell completions
+#[derive(Clone)]
+struct GradientName;
+
+impl clap::builder::TypedValueParser for GradientName {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        clap::builder::StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        Some(Box::new(
+            PRESETS
+                .iter()
+                .copied()
+                .map(clap::builder::PossibleValue::new),
+        ))
+    }
+}
+
+fn parse_range(s: &str) -> Result<(f32, f32), String> {
+    let (start, end) = s.split_once("..").ok_or("expected START..END")?;
+    let parse = |s: &str| s.trim().parse::<f32>().map_err(|e| e.to_string());
+    Ok((parse(start)?, parse(end)?))
+}
+
 #[test]
 fn verify_cli() {
     use clap::CommandFactory;
     Opt::command().debug_assert()
 }
+
+#[test]
+fn gradient_names() {
+    use clap::CommandFactory;
+    let cmd = Opt::command();
+    let arg = cmd.get_arguments().find(|arg| arg.get_id() == "gradient");
+    let names: Vec<_> = arg
+        .unwrap()
+        .get_possible_values()
+        .iter()
+        .map(|value| value.get_name().to_string())
+        .collect();
+    assert_eq!(names, PRESETS);
+    // gradients of the config file too
+    let opt = Opt::try_parse_from(["lolcrab", "-g", "brand"]).unwrap();
+    assert_eq!(opt.gradient, "brand");
+}
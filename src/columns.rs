@@ -0,0 +1,275 @@
+use std::io::{BufRead, Write};
+
+use bstr::{io::BufReadExt, ByteSlice};
+use regex::bytes::Regex;
+use unicode_width::UnicodeWidthChar;
+
+use crate::Lolcrab;
+
+// Colors of neighboring columns are far apart on the gradient
+const GOLDEN_RATIO: f32 = 0.618_034;
+
+/// How [`Lolcrab::colorize_columns`] splits lines into columns
+#[derive(Debug, Clone)]
+pub enum Columns {
+    /// Comma separated values, commas inside double quotes don't separate
+    Csv,
+    /// Tab separated values
+    Tsv,
+    /// Runs of spaces and tabs, like the output of `ps` or `ls -l`. The
+    /// columns are the words of the first line that isn't blank, usually a
+    /// header, every word of a later line gets the column above it. So a
+    /// column with spaces in it, like the command of `ps aux`, keeps its color.
+    Whitespace,
+    /// Separators matching the regular expression
+    Regex(Regex),
+}
+
+impl Columns {
+    // Byte offsets where the columns after the first start, the separator
+    // belongs to the column before it. Whitespace columns are found by
+    // `Lolcrab::words` instead, they depend on the header.
+    fn starts(&self, line: &[u8]) -> Vec<usize> {
+        match self {
+            Self::Csv => {
+                let mut quoted = false;
+                let mut starts = Vec::new();
+                for (i, &b) in line.iter().enumerate() {
+                    match b {
+                        b'"' => quoted = !quoted,
+                        b',' if !quoted => starts.push(i + 1),
+                        _ => {}
+                    }
+                }
+                starts
+            }
+            Self::Tsv => line.find_iter("\t").map(|i| i + 1).collect(),
+            Self::Whitespace => Vec::new(),
+            Self::Regex(re) => re
+                .find_iter(line)
+                .filter(|m| !m.is_empty())
+                .map(|m| m.end())
+                .collect(),
+        }
+    }
+}
+
+// A run of characters other than whitespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Word {
+    // byte offset in the line
+    offset: usize,
+    // display columns
+    start: usize,
+    end: usize,
+}
+
+impl Word {
+    // The header word above it, or the last one starting before it
+    fn column(self, header: &[Self]) -> usize {
+        header
+            .iter()
+            .position(|h| h.start < self.end && self.start < h.end)
+            .or_else(|| header.iter().rposition(|h| h.start <= self.start))
+            .unwrap_or(0)
+    }
+}
+
+impl Lolcrab {
+    /// Colorize tabular input, with one color for every column instead of
+    /// the colors of the noise or linear gradient, so the columns stay apart.
+    /// The text is not changed, aligned columns stay aligned.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `input` or `out` cause I/O errors
+    pub fn colorize_columns(
+        &mut self,
+        input: &mut impl BufRead,
+        out: &mut impl Write,
+        columns: &Columns,
+    ) -> std::io::Result<()> {
+        let offset = self.offset;
+        let position = |column: usize| (offset + column as f32 * GOLDEN_RATIO).fract();
+
+        // the colors are reset after every line, the last one may have no line break
+        let mut dirty = false;
+        let mut header = Vec::new();
+        let result = input.for_byte_line_with_terminator(|line| {
+            let starts: Vec<(usize, usize)> = if let Columns::Whitespace = columns {
+                let words = self.words(line);
+                if header.is_empty() {
+                    header.clone_from(&words);
+                }
+                words
+                    .iter()
+                    .map(|word| (word.offset, word.column(&header)))
+                    .collect()
+            } else {
+                columns.starts(line).into_iter().zip(1..).collect()
+            };
+            let mut starts = starts.into_iter().peekable();
+            let mut escaping = false;
+            self.column = Some(position(0));
+            for (start, _, grapheme) in line.grapheme_indices() {
+                while let Some((_, column)) = starts.next_if(|&(i, _)| i <= start) {
+                    self.column = Some(position(column));
+                }
+                escaping = self.colorize_grapheme(out, grapheme, escaping)?;
+            }
+            dirty = !line.ends_with(b"\n");
+            if !dirty {
+                self.write_reset(out)?;
+            }
+            Ok(true)
+        });
+        self.column = None;
+        result?;
+        if dirty {
+            self.write_reset(out)?;
+        }
+        out.flush()
+    }
+
+    // Words of the line with their display columns, tabs are expanded and
+    // escape sequences take no space
+    fn words(&self, line: &[u8]) -> Vec<Word> {
+        let mut words = Vec::new();
+        let mut word: Option<Word> = None;
+        let mut width = 0;
+        let mut escaping = false;
+        for (offset, _, g) in line.grapheme_indices() {
+            if g == "\x1B" {
+                escaping = true;
+                continue;
+            }
+            if escaping {
+                escaping = g.len() != 1 || !g.as_bytes()[0].is_ascii_alphabetic();
+                continue;
+            }
+            let start = width;
+            if g == "\t" {
+                width += self.tab_width as usize - width % self.tab_width as usize;
+            } else {
+                width += g
+                    .chars()
+                    .next()
+                    .and_then(UnicodeWidthChar::width)
+                    .unwrap_or(0);
+            }
+            if g.chars().all(char::is_whitespace) {
+                words.extend(word.take());
+            } else {
+                word.get_or_insert(Word {
+                    offset,
+                    start,
+                    end: width,
+                })
+                .end = width;
+            }
+        }
+        words.extend(word);
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::bytes::Regex;
+
+    use super::Columns;
+    use crate::tests::render;
+    use crate::Lolcrab;
+
+    #[test]
+    fn starts() {
+        let starts = |columns: Columns, line: &str| columns.starts(line.as_bytes());
+
+        assert_eq!(starts(Columns::Csv, "a,\"b,c\",d\n"), [2, 8]);
+        assert_eq!(starts(Columns::Csv, "\"a \"\"x\"\", b\",c"), [13]);
+        assert_eq!(starts(Columns::Tsv, "a\tb\t\tc"), [2, 4, 5]);
+        let re = Regex::new(r"\s*\|\s*").unwrap();
+        assert_eq!(starts(Columns::Regex(re), "a | b|c"), [4, 6]);
+    }
+
+    #[test]
+    fn words() {
+        let lol = Lolcrab::with_seed(1);
+        let words = |line: &str| {
+            lol.words(line.as_bytes())
+                .iter()
+                .map(|word| (word.offset, word.start, word.end))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            words("  PID TTY   CMD  \n"),
+            [(2, 2, 5), (6, 6, 9), (12, 12, 15)]
+        );
+        assert_eq!(words("\t \n"), []);
+        // tabs, wide characters and escape sequences
+        assert_eq!(
+            words("a\t蟹 \x1B[1mb\x1B[0m\n"),
+            [(0, 0, 1), (2, 4, 6), (10, 7, 8)]
+        );
+    }
+
+    #[test]
+    fn colorize_columns() {
+        let colorize = |text: &str| {
+            let mut lol = Lolcrab::with_seed(1);
+            let mut out = Vec::new();
+            lol.colorize_columns(&mut text.as_bytes(), &mut out, &Columns::Whitespace)
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let out = colorize("PID   CMD\n1     init\n");
+
+        // the text is unchanged
+        let mut plain = String::new();
+        let mut escaping = false;
+        for c in out.chars() {
+            if c == '\x1B' {
+                escaping = true;
+            } else if escaping {
+                escaping = !c.is_ascii_alphabetic();
+            } else {
+                plain.push(c);
+            }
+        }
+        assert_eq!(plain, "PID   CMD\n1     init\n");
+
+        // the colors are reset after every line
+        assert_eq!(out.matches("\n\x1B[39m").count(), 2);
+        assert!(out.ends_with("\n\x1B[39m"));
+        assert!(colorize("PID").ends_with("D\x1B[39m"));
+
+        // every column has its own color, the same in every line
+        let chars = render(out.as_bytes());
+        let text: String = chars.iter().map(|(c, _)| c).collect();
+        assert_eq!(text, "PIDCMD1init");
+        let color = |i: usize| chars[i].1.clone().unwrap();
+        assert_eq!(color(0), color(2));
+        assert_eq!(color(3), color(5));
+        assert_ne!(color(0), color(3));
+        assert_eq!(color(0), color(6));
+        assert_eq!(color(3), color(7));
+
+        // numbers wider than their header and columns with spaces keep the
+        // color of the header above them
+        let out = colorize("USER    PID CMD\nroot      1 init splash\nalice 12345 vim a\n");
+        let chars = render(out.as_bytes());
+        let text: String = chars.iter().map(|(c, _)| c).collect();
+        assert_eq!(text, "USERPIDCMDroot1initsplashalice12345vima");
+        let color = |i: usize| chars[i].1.clone().unwrap();
+        let (user, pid, cmd) = (color(0), color(4), color(7));
+        assert_ne!(user, pid);
+        assert_ne!(pid, cmd);
+        for (i, column) in [(10, &user), (14, &pid), (15, &cmd), (19, &cmd)] {
+            assert_eq!(&color(i), column);
+        }
+        for (i, column) in [(25, &user), (30, &pid), (35, &cmd), (38, &cmd)] {
+            assert_eq!(&color(i), column);
+        }
+    }
+}
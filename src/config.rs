@@ -0,0 +1,1012 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use colorgrad::Color;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::builder::check;
+use crate::gradient::{ClampedGradient, Gradient, ParseGradientError, SubGradient};
+use crate::palette::RandomScheme;
+use crate::{BuildError, Lolcrab, LolcrabBuilder, Settings};
+
+#[cfg(feature = "cli")]
+use crate::{BorderStyle, Columns, Frame, Opt};
+
+/// Error returned by [`Config::build`]
+#[derive(Debug, Clone)]
+pub enum ConfigError {
+    /// Unknown preset gradient
+    Preset(ParseGradientError),
+    /// Invalid CSS gradient or colors
+    Gradient(colorgrad::GradientBuilderError),
+    /// Invalid GIMP gradient
+    Ggr(String),
+    /// Invalid parameter
+    Build(BuildError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preset(e) => e.fmt(f),
+            Self::Gradient(e) => write!(f, "invalid gradient: {e}"),
+            Self::Ggr(e) => write!(f, "invalid GIMP gradient: {e}"),
+            Self::Build(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Preset(e) => Some(e),
+            Self::Gradient(e) => Some(e),
+            Self::Ggr(_) => None,
+            Self::Build(e) => Some(e),
+        }
+    }
+}
+
+impl From<ParseGradientError> for ConfigError {
+    fn from(e: ParseGradientError) -> Self {
+        Self::Preset(e)
+    }
+}
+
+impl From<colorgrad::GradientBuilderError> for ConfigError {
+    fn from(e: colorgrad::GradientBuilderError) -> Self {
+        Self::Gradient(e)
+    }
+}
+
+impl From<BuildError> for ConfigError {
+    fn from(e: BuildError) -> Self {
+        Self::Build(e)
+    }
+}
+
+/// Color blending mode of custom gradients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum BlendMode {
+    Rgb,
+    LinearRgb,
+    #[default]
+    Oklab,
+}
+
+impl From<BlendMode> for colorgrad::BlendMode {
+    fn from(mode: BlendMode) -> Self {
+        match mode {
+            BlendMode::Rgb => Self::Rgb,
+            BlendMode::LinearRgb => Self::LinearRgb,
+            BlendMode::Oklab => Self::Oklab,
+        }
+    }
+}
+
+/// Interpolation between the colors of custom gradients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Interpolation {
+    Linear,
+    Basis,
+    #[default]
+    CatmullRom,
+}
+
+impl Interpolation {
+    fn build(
+        self,
+        builder: &mut colorgrad::GradientBuilder,
+    ) -> Result<Box<dyn colorgrad::Gradient + Send + Sync>, colorgrad::GradientBuilderError> {
+        Ok(match self {
+            Self::Linear => Box::new(builder.build::<colorgrad::LinearGradient>()?),
+            Self::Basis => Box::new(builder.build::<colorgrad::BasisGradient>()?),
+            Self::CatmullRom => Box::new(builder.build::<colorgrad::CatmullRomGradient>()?),
+        })
+    }
+}
+
+/// User-defined gradient, see [`Config::gradients`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(default, rename_all = "kebab-case", deny_unknown_fields)
+)]
+pub struct CustomGradient {
+    /// Gradient in CSS gradient format, used instead of `colors` if set
+    pub css: Option<String>,
+    /// Colors in CSS color format
+    pub colors: Vec<String>,
+    /// Position of each color (0..=1), evenly spaced if empty
+    pub positions: Vec<f32>,
+    pub blend_mode: BlendMode,
+    pub interpolation: Interpolation,
+}
+
+impl CustomGradient {
+    /// # Errors
+    ///
+    /// Will return `Err` if a color or the CSS gradient is invalid, or the
+    /// number of positions doesn't match the number of colors
+    pub fn build(
+        &self,
+    ) -> Result<Box<dyn colorgrad::Gradient + Send + Sync>, colorgrad::GradientBuilderError> {
+        let mut builder = colorgrad::GradientBuilder::new();
+        // the mode is used while parsing CSS, so it is set first
+        builder.mode(self.blend_mode.into());
+        if let Some(css) = &self.css {
+            builder.css(css);
+        } else {
+            builder.html_colors(&self.colors);
+            if !self.positions.is_empty() {
+                builder.domain(&self.positions);
+            }
+        }
+
+        self.interpolation.build(&mut builder)
+    }
+}
+
+/// Where the colors come from
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum GradientSpec {
+    /// Name of a gradient in [`Config::gradients`], or of a preset gradient, see [`Gradient`]
+    Preset(String),
+    /// Custom gradient in CSS gradient format
+    Css(String),
+    /// Number of random colors
+    Random(u8),
+    /// Gradient defined inline
+    Custom(CustomGradient),
+    /// Contents of a GIMP gradient (`.ggr`) file
+    Ggr(String),
+}
+
+impl Default for GradientSpec {
+    fn default() -> Self {
+        Self::Preset(Gradient::Rainbow.name().to_string())
+    }
+}
+
+impl GradientSpec {
+    fn to_gradient(
+        &self,
+        config: &Config,
+        rng: &mut fastrand::Rng,
+    ) -> Result<Box<dyn colorgrad::Gradient + Send + Sync>, ConfigError> {
+        // blend mode and interpolation of CSS and random gradients
+        let mut builder = colorgrad::GradientBuilder::new();
+        builder.mode(config.blend_mode.into());
+
+        match self {
+            Self::Preset(name) => match config.gradients.get(name) {
+                Some(custom) => Ok(custom.build()?),
+                None => Ok(name.parse::<Gradient>()?.to_gradient()),
+            },
+            Self::Css(css) => Ok(config.interpolation.build(builder.css(css))?),
+            Self::Random(n) => {
+                let colors = config.random_scheme.colors(*n, rng);
+                Ok(config.interpolation.build(builder.colors(&colors))?)
+            }
+            Self::Custom(custom) => Ok(custom.build()?),
+            Self::Ggr(ggr) => {
+                // colors for segments using the foreground or background color
+                let fg = Color::new(0.0, 0.0, 0.0, 1.0);
+                let bg = Color::new(1.0, 1.0, 1.0, 1.0);
+                let gradient = colorgrad::GimpGradient::new(ggr.as_bytes(), &fg, &bg)
+                    .map_err(|e| ConfigError::Ggr(e.to_string()))?;
+                // black outside of the domain otherwise
+                Ok(Box::new(ClampedGradient(gradient)))
+            }
+        }
+    }
+}
+
+/// How a position is mapped to a gradient color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Mode {
+    #[default]
+    Noise,
+    Linear,
+}
+
+/// Where the gradient continues at a new file or paragraph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Continuity {
+    /// Where the previous one ended
+    #[default]
+    Continue,
+    /// At the start position
+    Reset,
+    /// At a random position
+    Randomize,
+}
+
+/// Noise mode parameters
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "kebab-case"))]
+pub struct NoiseConfig {
+    /// Noise scale (0.001..=0.25)
+    pub scale: f64,
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        Self { scale: 0.034 }
+    }
+}
+
+/// Linear mode parameters
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "kebab-case"))]
+pub struct LinearConfig {
+    /// Angle in degrees (0..=360), random if not set
+    pub angle: Option<f32>,
+    /// Spread (0..=100)
+    pub spread: f32,
+    /// Gradient offset (0..=1), random if not set
+    pub offset: Option<f32>,
+}
+
+impl Default for LinearConfig {
+    fn default() -> Self {
+        Self {
+            angle: None,
+            spread: 13.0,
+            offset: None,
+        }
+    }
+}
+
+/// Animation parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "kebab-case"))]
+pub struct AnimationConfig {
+    /// Number of frames (1..=30)
+    pub duration: usize,
+    /// Milliseconds between frames (30..=200)
+    pub speed: u64,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            duration: 5,
+            speed: 150,
+        }
+    }
+}
+
+/// Complete `Lolcrab` configuration, independent of the CLI.
+///
+/// With the `serde` feature it can be stored in an application's own
+/// configuration file.
+///
+/// # Example
+///
+/// ```
+/// # use std::error::Error;
+/// use lolcrab::{Config, GradientSpec, Mode};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let config = Config {
+///     gradient: GradientSpec::Css("#f00, #ff0, #00f".to_string()),
+///     mode: Mode::Linear,
+///     seed: Some(7),
+///     ..Config::default()
+/// };
+///
+/// let mut lol = config.build()?.lolcrab();
+/// lol.colorize_str("Lolcrab is the best", &mut std::io::stdout())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "kebab-case"))]
+pub struct Config {
+    pub gradient: GradientSpec,
+    /// Blend mode of CSS and random gradients
+    pub blend_mode: BlendMode,
+    /// Interpolation of CSS and random gradients
+    pub interpolation: Interpolation,
+    /// Colors of [`GradientSpec::Random`]
+    pub random_scheme: RandomScheme,
+    /// Reverse the gradient
+    pub reverse: bool,
+    /// Use only a part of the gradient, e.g. `[0.2, 0.8]` (0..=1)
+    pub range: Option<[f32; 2]>,
+    /// Number of segments of a sharp gradient
+    pub sharp: Option<u16>,
+    /// Smoothness of the sharp gradient segments (0..=1)
+    pub smoothness: f32,
+    pub mode: Mode,
+    /// Position at the start of a paragraph
+    pub paragraph: Continuity,
+    /// Random seed, random if not set
+    pub seed: Option<u64>,
+    /// Colorize the background
+    pub invert: bool,
+    /// Only write colors that differ from the previous one
+    pub compress: bool,
+    /// Color tolerance for compression, in Oklab ΔE (0..=1)
+    pub tolerance: f32,
+    pub noise: NoiseConfig,
+    pub linear: LinearConfig,
+    pub animation: AnimationConfig,
+    /// User-defined gradients by name, they take precedence over the presets
+    pub gradients: BTreeMap<String, CustomGradient>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            gradient: GradientSpec::default(),
+            blend_mode: BlendMode::default(),
+            interpolation: Interpolation::default(),
+            random_scheme: RandomScheme::default(),
+            reverse: false,
+            range: None,
+            sharp: None,
+            smoothness: 0.15,
+            mode: Mode::default(),
+            paragraph: Continuity::default(),
+            seed: None,
+            invert: false,
+            compress: false,
+            tolerance: 0.0,
+            noise: NoiseConfig::default(),
+            linear: LinearConfig::default(),
+            animation: AnimationConfig::default(),
+            gradients: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// The colors of a [`GradientSpec::Random`] gradient, in CSS gradient format.
+    ///
+    /// With a seed they are the colors used by [`Config::build`], so a random
+    /// gradient can be kept as [`GradientSpec::Css`].
+    #[must_use]
+    pub fn random_css(&self) -> Option<String> {
+        let GradientSpec::Random(n) = self.gradient else {
+            return None;
+        };
+        let colors = self.random_scheme.colors(n, &mut self.rng());
+        let colors: Vec<_> = colors.iter().map(Color::to_css_hex).collect();
+        Some(colors.join(", "))
+    }
+
+    fn rng(&self) -> fastrand::Rng {
+        self.seed
+            .map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed)
+            .fork()
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the gradient can't be created or a parameter is out of range
+    pub fn build(&self) -> Result<Settings, ConfigError> {
+        let mut rng = self.rng();
+
+        let mut gradient = self.gradient.to_gradient(self, &mut rng)?;
+
+        let [start, end] = self.range.unwrap_or([0.0, 1.0]);
+        check("range start", start.into(), 0.0, 1.0)?;
+        check("range end", end.into(), 0.0, 1.0)?;
+        let (start, end) = if self.reverse {
+            (end, start)
+        } else {
+            (start, end)
+        };
+        if (start, end) != (0.0, 1.0) {
+            gradient = Box::new(SubGradient {
+                inner: gradient.into(),
+                start,
+                end,
+            });
+        }
+
+        if let Some(n) = self.sharp.filter(|&n| n > 1) {
+            check("smoothness", self.smoothness.into(), 0.0, 1.0)?;
+            gradient = Box::new(gradient.sharp(n, self.smoothness));
+        }
+
+        let mut builder = LolcrabBuilder::new()
+            .boxed_gradient(gradient)
+            .noise_scale(self.noise.scale)
+            .invert(self.invert)
+            .compress(self.compress)
+            .color_tolerance(self.tolerance)
+            .anim_duration(self.animation.duration)
+            .anim_speed(self.animation.speed)
+            .linear(self.mode == Mode::Linear)
+            .spread(self.linear.spread)
+            .paragraph_continuity(self.paragraph);
+        if let Some(seed) = self.seed {
+            builder = builder.seed(seed);
+        }
+        if let Some(angle) = self.linear.angle {
+            builder = builder.angle(angle);
+        }
+        if let Some(offset) = self.linear.offset {
+            builder = builder.offset(offset);
+        }
+        let mut settings = builder.build()?;
+        let gradient = GradientConfig {
+            gradient: self.gradient.clone(),
+            blend_mode: self.blend_mode,
+            interpolation: self.interpolation,
+            random_scheme: self.random_scheme,
+            reverse: self.reverse,
+            range: self.range,
+            sharp: self.sharp,
+            smoothness: self.smoothness,
+            gradients: self.gradients.clone(),
+        };
+        let lol = &mut settings.lol;
+        lol.gradient_config = Some((Arc::new(gradient), Arc::clone(&lol.gradient)));
+        Ok(settings)
+    }
+}
+
+/// The gradient of a [`Config`] with its modifiers
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientConfig {
+    pub gradient: GradientSpec,
+    pub blend_mode: BlendMode,
+    pub interpolation: Interpolation,
+    pub random_scheme: RandomScheme,
+    pub reverse: bool,
+    pub range: Option<[f32; 2]>,
+    pub sharp: Option<u16>,
+    pub smoothness: f32,
+    pub gradients: BTreeMap<String, CustomGradient>,
+}
+
+/// Noise function of a [`Lolcrab`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseType {
+    /// `OpenSimplex` noise, seeded by the random seed
+    OpenSimplex,
+    /// A noise function given to [`Lolcrab::new`] or [`LolcrabBuilder::noise`]
+    Custom,
+}
+
+/// The parameters of a [`Lolcrab`], returned by [`Lolcrab::describe`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Description {
+    /// The gradient, `None` unless the `Lolcrab` was created by [`Config::build`]
+    pub gradient: Option<GradientConfig>,
+    pub noise_type: NoiseType,
+    pub mode: Mode,
+    pub paragraph: Continuity,
+    pub seed: Option<u64>,
+    pub invert: bool,
+    pub compress: bool,
+    pub tolerance: f32,
+    pub noise: NoiseConfig,
+    /// With the angle and offset in use, even if they were chosen randomly
+    pub linear: LinearConfig,
+    pub animation: AnimationConfig,
+}
+
+impl Description {
+    /// A config building a `Lolcrab` with the same output.
+    ///
+    /// `None` if the gradient is unknown, the noise is [`NoiseType::Custom`]
+    /// or there is no seed.
+    #[must_use]
+    pub fn to_config(&self) -> Option<Config> {
+        let gradient = self.gradient.clone()?;
+        if self.noise_type != NoiseType::OpenSimplex || self.seed.is_none() {
+            return None;
+        }
+        Some(Config {
+            gradient: gradient.gradient,
+            blend_mode: gradient.blend_mode,
+            interpolation: gradient.interpolation,
+            random_scheme: gradient.random_scheme,
+            reverse: gradient.reverse,
+            range: gradient.range,
+            sharp: gradient.sharp,
+            smoothness: gradient.smoothness,
+            mode: self.mode,
+            paragraph: self.paragraph,
+            seed: self.seed,
+            invert: self.invert,
+            compress: self.compress,
+            tolerance: self.tolerance,
+            noise: self.noise.clone(),
+            linear: self.linear.clone(),
+            animation: self.animation.clone(),
+            gradients: gradient.gradients,
+        })
+    }
+}
+
+impl Lolcrab {
+    /// The parameters in use, including the randomly chosen angle and offset.
+    ///
+    /// The gradient is only known if this `Lolcrab` was created by
+    /// [`Config::build`] and the gradient hasn't been replaced since.
+    #[must_use]
+    pub fn describe(&self) -> Description {
+        // compares the data pointers only
+        fn same<T: ?Sized>(a: &Arc<T>, b: &Arc<T>) -> bool {
+            Arc::as_ptr(a).cast::<()>() == Arc::as_ptr(b).cast::<()>()
+        }
+
+        let gradient = self
+            .gradient_config
+            .as_ref()
+            .filter(|(_, gradient)| same(gradient, &self.gradient))
+            .map(|(config, _)| GradientConfig::clone(config));
+        let noise_type = match &self.builtin_noise {
+            Some(noise) if same(noise, &self.noise) => NoiseType::OpenSimplex,
+            _ => NoiseType::Custom,
+        };
+        Description {
+            gradient,
+            noise_type,
+            mode: if self.linear {
+                Mode::Linear
+            } else {
+                Mode::Noise
+            },
+            paragraph: self.paragraph,
+            seed: self.seed,
+            invert: self.invert,
+            compress: self.compress,
+            tolerance: self.tolerance,
+            noise: NoiseConfig {
+                scale: self.noise_scale,
+            },
+            linear: LinearConfig {
+                angle: Some(self.angle),
+                spread: self.spread,
+                offset: Some(self.offset),
+            },
+            animation: AnimationConfig {
+                duration: self.anim_duration,
+                speed: u64::try_from(self.anim_sleep.as_millis()).unwrap_or(u64::MAX),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl From<&Opt> for Config {
+    fn from(opt: &Opt) -> Self {
+        let gradient = if let Some(css) = &opt.custom {
+            GradientSpec::Css(css.clone())
+        } else if let Some(n) = opt.random_colors {
+            GradientSpec::Random(n)
+        } else if opt.lolcat {
+            GradientSpec::Preset(Gradient::Lolcat.name().to_string())
+        } else {
+            GradientSpec::Preset(opt.gradient.clone())
+        };
+
+        let linear = opt.linear
+            || opt.fit
+            || opt.stretch.is_some()
+            || opt.lolcat
+            || opt.angle.is_some()
+            || opt.spread.is_some()
+            || opt.offset.is_some();
+
+        let defaults = Self::default();
+        let blend_mode = match opt.blend_mode.as_deref() {
+            Some("rgb") => BlendMode::Rgb,
+            Some("linear-rgb") => BlendMode::LinearRgb,
+            _ => BlendMode::Oklab,
+        };
+        let interpolation = match opt.interpolation.as_deref() {
+            Some("linear") => Interpolation::Linear,
+            Some("basis") => Interpolation::Basis,
+            _ => Interpolation::CatmullRom,
+        };
+
+        let random_scheme = match opt.random_scheme.as_deref() {
+            Some("analogous") => RandomScheme::Analogous,
+            Some("complementary") => RandomScheme::Complementary,
+            Some("triadic") => RandomScheme::Triadic,
+            Some("tetradic") => RandomScheme::Tetradic,
+            Some("pastel") => RandomScheme::Pastel,
+            Some("neon") => RandomScheme::Neon,
+            Some("monochrome") => RandomScheme::Monochrome,
+            _ => RandomScheme::Any,
+        };
+
+        Self {
+            gradient,
+            blend_mode,
+            interpolation,
+            random_scheme,
+            reverse: opt.reverse,
+            range: opt.range.map(|(start, end)| [start, end]),
+            sharp: opt.sharp.map(u16::from),
+            smoothness: opt.smoothness.unwrap_or(defaults.smoothness),
+            mode: if linear { Mode::Linear } else { Mode::Noise },
+            paragraph: continuity(opt.per_paragraph.as_deref()),
+            seed: opt.seed,
+            invert: opt.invert,
+            compress: opt.compress,
+            tolerance: opt.tolerance.unwrap_or(defaults.tolerance),
+            // the command line clamps values out of range, unlike the builder
+            noise: NoiseConfig {
+                scale: opt.scale.clamp(0.001, 0.25),
+            },
+            linear: LinearConfig {
+                angle: opt.angle.map(|angle| angle.rem_euclid(360.0)),
+                spread: opt
+                    .spread
+                    .map_or(defaults.linear.spread, |spread| spread.clamp(0.0, 100.0)),
+                offset: opt.offset.map(|offset| offset.clamp(0.0, 1.0)),
+            },
+            animation: AnimationConfig {
+                duration: opt
+                    .duration
+                    .map_or(defaults.animation.duration, |duration| {
+                        usize::from(duration.clamp(1, 30))
+                    }),
+                speed: opt.speed.map_or(defaults.animation.speed, |speed| {
+                    u64::from(speed.clamp(30, 200))
+                }),
+            },
+            gradients: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+fn continuity(name: Option<&str>) -> Continuity {
+    match name {
+        Some("reset") => Continuity::Reset,
+        Some("randomize") => Continuity::Randomize,
+        _ => Continuity::Continue,
+    }
+}
+
+#[cfg(feature = "cli")]
+impl TryFrom<Opt> for Lolcrab {
+    type Error = ConfigError;
+
+    /// The same as `Config::from(&opt).build()`
+    fn try_from(opt: Opt) -> Result<Self, Self::Error> {
+        Ok(Config::from(&opt).build()?.lolcrab())
+    }
+}
+
+#[cfg(feature = "cli")]
+impl Opt {
+    /// Position at the start of every file after the first
+    #[must_use]
+    pub fn file_continuity(&self) -> Continuity {
+        continuity(self.per_file.as_deref())
+    }
+
+    /// How `--columns` splits the lines
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the `--separator` is not a valid regular expression
+    pub fn columns(&self) -> Result<Option<Columns>, regex::Error> {
+        Ok(match self.columns.as_deref() {
+            Some("csv") => Some(Columns::Csv),
+            Some("tsv") => Some(Columns::Tsv),
+            Some("whitespace") => Some(Columns::Whitespace),
+            Some(_) => {
+                let separator = self.separator.as_deref().unwrap_or_default();
+                Some(Columns::Regex(regex::bytes::Regex::new(separator)?))
+            }
+            None => None,
+        })
+    }
+
+    /// The border of `--box`
+    #[must_use]
+    pub fn frame(&self) -> Option<Frame> {
+        let style = match self.frame.as_deref()? {
+            "double" => BorderStyle::Double,
+            "rounded" => BorderStyle::Rounded,
+            "heavy" => BorderStyle::Heavy,
+            "ascii" => BorderStyle::Ascii,
+            _ => BorderStyle::Single,
+        };
+        Some(Frame {
+            style,
+            padding: self.padding.unwrap_or(0),
+            title: self.title.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use colorgrad::Gradient as _;
+
+    use std::sync::Arc;
+
+    use super::{
+        BlendMode, Config, ConfigError, GradientSpec, Interpolation, Mode, NoiseType, RandomScheme,
+    };
+    use crate::Lolcrab;
+
+    #[test]
+    fn build() {
+        assert!(Config::default().build().is_ok());
+
+        let config = Config {
+            gradient: GradientSpec::Preset("foo".to_string()),
+            ..Config::default()
+        };
+        assert!(matches!(config.build(), Err(ConfigError::Preset(_))));
+
+        let config = Config {
+            gradient: GradientSpec::Css("#f00, nope".to_string()),
+            ..Config::default()
+        };
+        assert!(matches!(config.build(), Err(ConfigError::Gradient(_))));
+
+        let mut config = Config::default();
+        config.noise.scale = 1.0;
+        assert!(matches!(config.build(), Err(ConfigError::Build(_))));
+    }
+
+    #[test]
+    fn modifiers() {
+        let viridis = colorgrad::preset::viridis();
+        let gradient = |config: Config| config.build().unwrap().lolcrab().gradient;
+        let viridis_config = || Config {
+            gradient: GradientSpec::Preset("viridis".to_string()),
+            ..Config::default()
+        };
+
+        let reversed = gradient(Config {
+            reverse: true,
+            ..viridis_config()
+        });
+        assert_eq!(reversed.at(0.0), viridis.at(1.0));
+        assert_eq!(reversed.at(1.0), viridis.at(0.0));
+
+        let part = gradient(Config {
+            range: Some([0.2, 0.8]),
+            ..viridis_config()
+        });
+        assert_eq!(part.at(0.0), viridis.at(0.2));
+        assert_eq!(part.at(1.0), viridis.at(0.8));
+        assert_eq!(part.at(-0.1), viridis.at(0.2));
+        assert_eq!(part.at(1.1), viridis.at(0.8));
+
+        let part = gradient(Config {
+            range: Some([0.2, 0.8]),
+            reverse: true,
+            ..viridis_config()
+        });
+        assert_eq!(part.at(0.0), viridis.at(0.8));
+        assert_eq!(part.at(1.1).to_rgba8(), viridis.at(0.2).to_rgba8());
+
+        let css = gradient(Config {
+            gradient: GradientSpec::Css("#f00, #00f".to_string()),
+            blend_mode: BlendMode::Rgb,
+            interpolation: Interpolation::Linear,
+            ..Config::default()
+        });
+        assert_eq!(css.at(0.5).to_rgba8(), [128, 0, 128, 255]);
+
+        for config in [
+            Config {
+                range: Some([0.5, 1.5]),
+                ..Config::default()
+            },
+            Config {
+                sharp: Some(5),
+                smoothness: 2.0,
+                ..Config::default()
+            },
+        ] {
+            assert!(matches!(config.build(), Err(ConfigError::Build(_))));
+        }
+    }
+
+    #[test]
+    fn ggr() {
+        let ggr = "GIMP Gradient\nName: Test\n1\n0 0.5 1 1 0 0 1 0 0 1 1 0 0\n";
+        let gradient = Config {
+            gradient: GradientSpec::Ggr(ggr.to_string()),
+            ..Config::default()
+        }
+        .build()
+        .unwrap()
+        .lolcrab()
+        .gradient;
+
+        // noise mode samples a bit outside of 0..1
+        assert_eq!(gradient.at(-0.1).to_rgba8(), [255, 0, 0, 255]);
+        assert_eq!(gradient.at(1.1).to_rgba8(), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn random_css() {
+        assert_eq!(Config::default().random_css(), None);
+
+        let config = Config {
+            gradient: GradientSpec::Random(4),
+            random_scheme: RandomScheme::Triadic,
+            seed: Some(5),
+            ..Config::default()
+        };
+        let css = config.random_css().unwrap();
+        assert_eq!(css.split(", ").count(), 4);
+
+        let random = config.build().unwrap().lolcrab().gradient;
+        let saved = Config {
+            gradient: GradientSpec::Css(css),
+            ..Config::default()
+        };
+        let saved = saved.build().unwrap().lolcrab().gradient;
+        for t in [0.0, 1.0] {
+            assert_eq!(random.at(t).to_rgba8(), saved.at(t).to_rgba8());
+        }
+    }
+
+    #[test]
+    fn describe() {
+        let text = "Lolcrab\nis the best";
+        for mode in [Mode::Noise, Mode::Linear] {
+            let config = Config {
+                gradient: GradientSpec::Preset("viridis".to_string()),
+                mode,
+                seed: Some(11),
+                invert: true,
+                ..Config::default()
+            };
+            let lol = config.build().unwrap().lolcrab();
+
+            let described = lol.describe();
+            assert_eq!(described.mode, mode);
+            assert_eq!(described.seed, Some(11));
+            assert_eq!(described.noise_type, NoiseType::OpenSimplex);
+            assert!(described.invert);
+            assert!(described.linear.angle.is_some());
+            assert!(described.linear.offset.is_some());
+            let gradient = described.gradient.as_ref().unwrap();
+            assert_eq!(gradient.gradient, config.gradient);
+
+            let mut out1 = Vec::new();
+            lol.clone().colorize_str(text, &mut out1).unwrap();
+
+            // explicit angle and offset instead of the random ones
+            let config = described.to_config().unwrap();
+            assert_eq!(config.linear, described.linear);
+            let mut out2 = Vec::new();
+            let mut lol = config.build().unwrap().lolcrab();
+            lol.colorize_str(text, &mut out2).unwrap();
+
+            assert_eq!(out1, out2);
+        }
+    }
+
+    #[test]
+    fn describe_unknown() {
+        // the gradient and noise can't be described
+        let mut lol = Lolcrab::new(
+            Some(Box::new(colorgrad::preset::viridis())),
+            Some(Box::new(noise::OpenSimplex::new(1))),
+        );
+        let described = lol.describe();
+        assert_eq!(described.gradient, None);
+        assert_eq!(described.noise_type, NoiseType::Custom);
+        assert_eq!(described.to_config(), None);
+
+        // a gradient replaced after building
+        let config = Config {
+            seed: Some(3),
+            ..Config::default()
+        };
+        lol = config.build().unwrap().lolcrab();
+        assert_eq!(lol.describe().to_config().unwrap().seed, Some(3));
+        lol.gradient = Arc::new(colorgrad::preset::viridis());
+        assert_eq!(lol.describe().gradient, None);
+    }
+
+    #[test]
+    fn same_output() {
+        let text = "Lolcrab\nis the best";
+        let config = Config {
+            gradient: GradientSpec::Random(5),
+            mode: Mode::Linear,
+            seed: Some(1),
+            ..Config::default()
+        };
+
+        let mut out1 = Vec::new();
+        let mut lol = config.build().unwrap().lolcrab();
+        lol.colorize_str(text, &mut out1).unwrap();
+
+        let mut out2 = Vec::new();
+        let mut lol = config.build().unwrap().lolcrab();
+        lol.colorize_str(text, &mut out2).unwrap();
+
+        assert_eq!(out1, out2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let config: Config = toml::from_str(
+            r##"
+            gradient = { css = "#f00, #00f" }
+            mode = "linear"
+            seed = 9
+
+            [linear]
+            angle = 45
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(config.gradient, GradientSpec::Css("#f00, #00f".to_string()));
+        assert_eq!(config.mode, Mode::Linear);
+        assert_eq!(config.seed, Some(9));
+        assert_eq!(config.linear.angle, Some(45.0));
+        assert_eq!(config.linear.spread, 13.0);
+
+        let s = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&s).unwrap(), config);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn from_opt() {
+        use clap::Parser;
+
+        use std::convert::TryFrom;
+
+        use crate::Opt;
+
+        // values out of range are clamped, like before the builder
+        let opt = Opt::parse_from([
+            "lolcrab", "-s", "5", "--speed", "10", "-d", "50", "-A", "-30", "--offset", "2",
+        ]);
+        let config = Config::from(&opt);
+        assert_eq!(config.noise.scale, 0.25);
+        assert_eq!(config.animation.speed, 30);
+        assert_eq!(config.animation.duration, 30);
+        assert_eq!(config.linear.angle, Some(330.0));
+        assert_eq!(config.linear.offset, Some(1.0));
+        assert!(config.build().is_ok());
+
+        let opt = Opt::parse_from(["lolcrab", "--angle", "400"]);
+        assert_eq!(Config::from(&opt).linear.angle, Some(40.0));
+        assert!(Lolcrab::try_from(opt).is_ok());
+
+        let opt = Opt::parse_from(["lolcrab", "--custom", "no-such-color"]);
+        assert!(matches!(
+            Lolcrab::try_from(opt),
+            Err(ConfigError::Gradient(_))
+        ));
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use lolcrab::{
+    BlendMode, Config, Continuity, CustomGradient, Font, GradientSpec, Interpolation, Lolcrab,
+    Mode, Opt, RandomScheme,
+};
+use serde::{de, Deserialize};
+use toml::Table;
+
+use crate::error_reason;
+
+/// Error reading the config file or a gradient file
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    /// Invalid syntax or value, lines and columns start at 1
+    Parse {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    /// A table where an option value is expected
+    Value(PathBuf, String),
+    /// The selected profile does not exist
+    Profile(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "{}: {}", path.display(), error_reason(e)),
+            Self::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => {
+                write!(f, "{}", path.display())?;
+                if let Some(line) = line {
+                    write!(f, ":{line}")?;
+                }
+                if let Some(column) = column {
+                    write!(f, ":{column}")?;
+                }
+                write!(f, ": {message}")
+            }
+            Self::Value(path, key) => {
+                write!(f, "{}: invalid value for '{key}'", path.display())
+            }
+            Self::Profile(name) => write!(f, "unknown profile '{name}'"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Contents of the config file
+#[derive(Debug, Default)]
+pub struct ConfigFile {
+    /// Options as command line arguments
+    pub args: Vec<OsString>,
+    /// Gradients from the `[gradients.NAME]` tables
+    pub gradients: BTreeMap<String, CustomGradient>,
+}
+
+pub fn path() -> Option<PathBuf> {
+    std::env::var("LOLCRAB_CONFIG_PATH")
+        .ok()
+        .map(PathBuf::from)
+        .filter(|config_path| config_path.is_file())
+        .or_else(|| Some(dirs::config_dir()?.join("lolcrab").join("config")))
+}
+
+/// Read the config file, with the options of `profile` on top of the default ones.
+pub fn read(profile: Option<&str>) -> Result<ConfigFile, Error> {
+    let Some(path) = path().filter(|path| path.exists()) else {
+        return profile.map_or_else(
+            || Ok(ConfigFile::default()),
+            |name| Err(unknown_profile(name)),
+        );
+    };
+    let s = fs::read_to_string(&path).map_err(|e| Error::Io(path.clone(), e))?;
+    parse(&path, &s, profile)
+}
+
+fn parse(path: &Path, s: &str, profile: Option<&str>) -> Result<ConfigFile, Error> {
+    if is_args_style(s) {
+        // the old format has no profiles
+        if let Some(name) = profile {
+            return Err(unknown_profile(name));
+        }
+        return Ok(ConfigFile {
+            args: parse_args(path, s)?,
+            gradients: BTreeMap::new(),
+        });
+    }
+    parse_toml(path, s, profile)
+}
+
+fn unknown_profile(name: &str) -> Error {
+    Error::Profile(name.to_string())
+}
+
+// The old format is a list of command line arguments. Only the first line
+// that isn't a comment tells, a TOML file can't start with a `-`.
+fn is_args_style(s: &str) -> bool {
+    s.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| line.starts_with('-'))
+}
+
+fn parse_args(path: &Path, s: &str) -> Result<Vec<OsString>, Error> {
+    let mut args = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(line_args) = shlex::split(line) else {
+            return Err(Error::Parse {
+                path: path.to_path_buf(),
+                line: Some(i + 1),
+                column: None,
+                message: "unbalanced quotes".to_string(),
+            });
+        };
+        args.extend(line_args.into_iter().map(OsString::from));
+    }
+    Ok(args)
+}
+
+// Value of an option, tables are only valid for `profile` and `gradients`.
+// Other values are rejected while parsing, so the error has a location.
+enum Value {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Table,
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Value;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string, number or boolean")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+                Ok(Value::Bool(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+                Ok(Value::Integer(v))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+                Ok(Value::Float(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+                Ok(Value::String(v.to_string()))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+                while map
+                    .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
+                    .is_some()
+                {}
+                Ok(Value::Table)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct Sections {
+    #[serde(default)]
+    profile: BTreeMap<String, BTreeMap<String, Value>>,
+    #[serde(default)]
+    gradients: BTreeMap<String, CustomGradient>,
+}
+
+// Keys are long option names, `[profile.NAME]` tables override the top level keys
+fn parse_toml(path: &Path, s: &str, profile: Option<&str>) -> Result<ConfigFile, Error> {
+    let toml_error = |e| toml_error(path, s, &e);
+    let mut options: BTreeMap<String, Value> = toml::from_str(s).map_err(toml_error)?;
+    let Sections {
+        profile: mut profiles,
+        gradients,
+    } = toml::from_str(s).map_err(toml_error)?;
+    options.remove("profile");
+    options.remove("gradients");
+
+    if let Some(name) = profile {
+        let profile = profiles.remove(name).ok_or_else(|| unknown_profile(name))?;
+        options.extend(profile);
+    }
+
+    let mut args = Vec::new();
+    for (key, value) in options {
+        let value = match value {
+            Value::Bool(true) => {
+                args.push(format!("--{key}").into());
+                continue;
+            }
+            Value::Bool(false) => continue,
+            Value::String(s) => s,
+            Value::Integer(n) => n.to_string(),
+            Value::Float(n) => n.to_string(),
+            Value::Table => return Err(Error::Value(path.to_path_buf(), key)),
+        };
+        args.push(format!("--{key}={value}").into());
+    }
+    Ok(ConfigFile { args, gradients })
+}
+
+/// Error with the location in `s`, the contents of the file at `path`
+pub fn toml_error(path: &Path, s: &str, e: &toml::de::Error) -> Error {
+    let (line, column) = e
+        .span()
+        .map_or((None, None), |span| line_column(s, span.start));
+    Error::Parse {
+        path: path.to_path_buf(),
+        line,
+        column,
+        message: e.message().trim().replace('\n', ", "),
+    }
+}
+
+fn line_column(s: &str, offset: usize) -> (Option<usize>, Option<usize>) {
+    let before = &s[..offset.min(s.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    (Some(line), Some(column))
+}
+
+/// The options of `config` and the resolved parameters of `lol`, as a config
+/// file that gives the same output. `config` needs a seed for that.
+pub fn dump(config: &Config, lol: &Lolcrab, opt: &Opt) -> String {
+    let mut table = Table::new();
+    dump_gradient(&mut table, config, opt);
+    let mut set = |key: &str, value: toml::Value| table.insert(key.to_string(), value);
+
+    let resolved = lol.describe();
+    set("scale", resolved.noise.scale.into());
+    if let Some(seed) = resolved.seed {
+        // TOML integers are signed
+        let seed = i64::try_from(seed).map_or_else(|_| seed.to_string().into(), toml::Value::from);
+        set("seed", seed);
+    }
+    if resolved.invert {
+        set("invert", true.into());
+    }
+    if resolved.compress {
+        set("compress", true.into());
+        set("tolerance", float(resolved.tolerance));
+    }
+    if resolved.mode == Mode::Linear {
+        set("linear", true.into());
+        if let Some(angle) = resolved.linear.angle {
+            set("angle", float(angle));
+        }
+        set("spread", float(resolved.linear.spread));
+        if let Some(offset) = resolved.linear.offset {
+            set("offset", float(offset));
+        }
+    }
+    if opt.banner {
+        set("banner", true.into());
+    }
+    if let Some(font) = &opt.font {
+        let font = match Font::builtin(font) {
+            Some(_) => font.clone(),
+            None => fs::canonicalize(font)
+                .map_or_else(|_| font.clone(), |path| path.to_string_lossy().into_owned()),
+        };
+        set("font", font.into());
+    }
+    if let Some(style) = &opt.frame {
+        set("box", style.as_str().into());
+        if let Some(padding) = opt.padding {
+            set("padding", i64::try_from(padding).unwrap_or(i64::MAX).into());
+        }
+        if let Some(title) = &opt.title {
+            set("title", title.as_str().into());
+        }
+    }
+    if let Some(columns) = &opt.columns {
+        set("columns", columns.as_str().into());
+        if let Some(separator) = &opt.separator {
+            set("separator", separator.as_str().into());
+        }
+    }
+    if opt.fit {
+        set("fit", true.into());
+    }
+    if let Some(width) = opt.stretch {
+        set("stretch", i64::try_from(width).unwrap_or(i64::MAX).into());
+    }
+    let continuity = |continuity| match continuity {
+        Continuity::Continue => None,
+        Continuity::Reset => Some("reset"),
+        Continuity::Randomize => Some("randomize"),
+    };
+    if let Some(name) = continuity(opt.file_continuity()) {
+        set("per-file", name.into());
+    }
+    if let Some(name) = continuity(resolved.paragraph) {
+        set("per-paragraph", name.into());
+    }
+    if opt.animate {
+        set("animate", true.into());
+        let duration = i64::try_from(resolved.animation.duration).unwrap_or(i64::MAX);
+        set("duration", duration.into());
+        let speed = i64::try_from(resolved.animation.speed).unwrap_or(i64::MAX);
+        set("speed", speed.into());
+    }
+
+    toml::to_string(&table).unwrap_or_default()
+}
+
+// The gradient options, and the table of a gradient from the config file
+fn dump_gradient(table: &mut Table, config: &Config, opt: &Opt) {
+    let mut set = |key: &str, value: toml::Value| table.insert(key.to_string(), value);
+
+    if let GradientSpec::Css(_) | GradientSpec::Random(_) = config.gradient {
+        let blend_mode = match config.blend_mode {
+            BlendMode::Rgb => "rgb",
+            BlendMode::LinearRgb => "linear-rgb",
+            BlendMode::Oklab => "oklab",
+        };
+        set("blend-mode", blend_mode.into());
+        let interpolation = match config.interpolation {
+            Interpolation::Linear => "linear",
+            Interpolation::Basis => "basis",
+            Interpolation::CatmullRom => "catmull-rom",
+        };
+        set("interpolation", interpolation.into());
+    }
+    match &config.gradient {
+        GradientSpec::Preset(name) => {
+            set("gradient", name.as_str().into());
+        }
+        GradientSpec::Css(css) => {
+            set("custom", css.as_str().into());
+        }
+        // the same seed gives the same colors
+        GradientSpec::Random(n) => {
+            set("random-colors", i64::from(*n).into());
+            let scheme = match config.random_scheme {
+                RandomScheme::Any => "any",
+                RandomScheme::Analogous => "analogous",
+                RandomScheme::Complementary => "complementary",
+                RandomScheme::Triadic => "triadic",
+                RandomScheme::Tetradic => "tetradic",
+                RandomScheme::Pastel => "pastel",
+                RandomScheme::Neon => "neon",
+                RandomScheme::Monochrome => "monochrome",
+            };
+            set("random-scheme", scheme.into());
+        }
+        GradientSpec::Custom(_) | GradientSpec::Ggr(_) => {
+            if let Some(path) = &opt.gradient_file {
+                let path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+                set("gradient-file", path.to_string_lossy().as_ref().into());
+            }
+        }
+    }
+
+    if config.reverse {
+        set("reverse", true.into());
+    }
+    if let Some([start, end]) = config.range {
+        set("range", format!("{start}..{end}").into());
+    }
+    if let Some(n) = config.sharp {
+        set("sharp", i64::from(n).into());
+        set("smoothness", float(config.smoothness));
+    }
+
+    // a gradient from the config file
+    if let GradientSpec::Preset(name) = &config.gradient {
+        if let Some(Ok(custom)) = config.gradients.get(name).map(toml::Value::try_from) {
+            let mut gradients = Table::new();
+            gradients.insert(name.clone(), custom);
+            table.insert("gradients".to_string(), gradients.into());
+        }
+    }
+}
+
+// The shortest decimal that is read back as the same `f32`
+fn float(x: f32) -> toml::Value {
+    x.to_string()
+        .parse::<f64>()
+        .unwrap_or_else(|_| x.into())
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use clap::Parser;
+    use lolcrab::{BlendMode, Config, CustomGradient, Interpolation, Opt};
+
+    use super::{dump, ConfigFile, Error};
+
+    fn parse(s: &str, profile: Option<&str>) -> Result<ConfigFile, Error> {
+        super::parse(Path::new("config"), s, profile)
+    }
+
+    fn parse_err(s: &str) -> String {
+        parse(s, None).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn args_style() {
+        let s = "# comment\n--gradient viridis\n-i --custom '#f00, #00f'\n";
+        assert_eq!(
+            parse(s, None).unwrap().args,
+            ["--gradient", "viridis", "-i", "--custom", "#f00, #00f"]
+        );
+        assert!(matches!(parse(s, Some("x")), Err(Error::Profile(_))));
+        assert_eq!(
+            parse_err("-i\n--custom '#f00\n"),
+            "config:2: unbalanced quotes"
+        );
+    }
+
+    #[test]
+    fn toml() {
+        let s = r#"
+            # comment
+            gradient = "viridis"
+            invert = true
+            scale = 0.05
+
+            [profile.lolcat]
+            lolcat = true
+            invert = false
+            speed = 40
+        "#;
+        assert_eq!(
+            parse(s, None).unwrap().args,
+            ["--gradient=viridis", "--invert", "--scale=0.05"]
+        );
+        assert_eq!(
+            parse(s, Some("lolcat")).unwrap().args,
+            [
+                "--gradient=viridis",
+                "--lolcat",
+                "--scale=0.05",
+                "--speed=40"
+            ]
+        );
+        assert!(matches!(parse(s, Some("x")), Err(Error::Profile(_))));
+        assert!(parse("", None).unwrap().args.is_empty());
+    }
+
+    #[test]
+    fn toml_errors() {
+        assert_eq!(
+            parse_err("invert = true\nangle = [1]\n"),
+            "config:2:9: invalid type: sequence, expected a string, number or boolean"
+        );
+        assert_eq!(
+            parse("[profile.x.y]\n", Some("x")).unwrap_err().to_string(),
+            "config: invalid value for 'y'"
+        );
+        assert!(parse_err("gradient = \n").starts_with("config:1:12: invalid string"));
+        assert_eq!(
+            parse_err("[linear]\nangle = 5\n"),
+            "config: invalid value for 'linear'"
+        );
+    }
+
+    #[test]
+    fn gradients() {
+        let s = r##"
+            gradient = "brand"
+
+            [gradients.brand]
+            colors = ["#f00", "gold", "#00f"]
+            positions = [0, 0.2, 1]
+            blend-mode = "rgb"
+            interpolation = "linear"
+
+            [gradients.css]
+            css = "#f00, 30%, #00f"
+        "##;
+        let config = parse(s, None).unwrap();
+        assert_eq!(config.args, ["--gradient=brand"]);
+        assert_eq!(
+            config.gradients["brand"],
+            CustomGradient {
+                css: None,
+                colors: vec!["#f00".into(), "gold".into(), "#00f".into()],
+                positions: vec![0.0, 0.2, 1.0],
+                blend_mode: BlendMode::Rgb,
+                interpolation: Interpolation::Linear,
+            }
+        );
+        assert_eq!(
+            config.gradients["css"].interpolation,
+            Interpolation::CatmullRom
+        );
+        assert!(config.gradients.values().all(|g| g.build().is_ok()));
+
+        // lines of an array starting with `-` don't make it the old format
+        let s = "[gradients.a]\ncolors = [\"#f00\", \"#00f\"]\npositions = [\n  -0,\n  1,\n]\n";
+        assert_eq!(parse(s, None).unwrap().gradients["a"].positions, [0.0, 1.0]);
+
+        assert!(parse_err("[gradients.a]\ncolour = \"#f00\"")
+            .starts_with("config:2:1: unknown field `colour`"));
+    }
+
+    #[test]
+    fn dump_config() {
+        let opt = Opt::parse_from(["lolcrab", "-g", "brand", "-l", "-S", "3", "--sharp", "4"]);
+        let mut config = Config::from(&opt);
+        config.gradients.insert(
+            "brand".to_string(),
+            CustomGradient {
+                colors: vec!["#f00".into(), "#00f".into()],
+                ..CustomGradient::default()
+            },
+        );
+        let lol = config.build().unwrap().lolcrab();
+        let described = lol.describe();
+
+        let dumped = parse(&dump(&config, &lol, &opt), None).unwrap();
+        let mut args = vec!["lolcrab".into()];
+        args.extend(dumped.args);
+        let mut reread = Config::from(&Opt::parse_from(args));
+        reread.gradients = dumped.gradients;
+
+        assert_eq!(reread.gradient, config.gradient);
+        assert_eq!(reread.gradients, config.gradients);
+        assert_eq!(reread.sharp, Some(4));
+        assert_eq!(reread.seed, Some(3));
+        assert_eq!(reread.linear, described.linear);
+    }
+}
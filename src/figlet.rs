@@ -0,0 +1,425 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+// Horizontal layout bits, as in the `full_layout` of the font header
+const EQUAL: i32 = 1;
+const LOWLINE: i32 = 2;
+const HIERARCHY: i32 = 4;
+const PAIR: i32 = 8;
+const BIG_X: i32 = 16;
+const HARDBLANK: i32 = 32;
+const KERNING: i32 = 64;
+const SMUSHING: i32 = 128;
+
+// Characters every font has after the printable ASCII ones
+const DEUTSCH: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+/// Error returned when parsing an invalid FIGlet font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFontError {
+    line: usize,
+    message: &'static str,
+}
+
+impl fmt::Display for ParseFontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid FIGlet font: {} (line {})",
+            self.message, self.line
+        )
+    }
+}
+
+impl error::Error for ParseFontError {}
+
+/// A [FIGlet](http://www.figlet.org) font, renders text in large letters.
+///
+/// Fonts in the `.flf` format can be parsed with [`str::parse`], a few are
+/// bundled, see [`Font::BUILTIN`]. Characters are joined using the kerning
+/// and smushing rules of the font.
+///
+/// # Example
+///
+/// ```
+/// # use std::error::Error;
+/// use lolcrab::{Font, Lolcrab};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let font = Font::builtin("standard").unwrap();
+/// let banner = font.render("Lolcrab");
+///
+/// let mut lol = Lolcrab::new(None, None);
+/// lol.colorize_str(&banner, &mut std::io::stdout())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    hardblank: char,
+    height: usize,
+    layout: i32,
+    glyphs: BTreeMap<char, Vec<Vec<char>>>,
+}
+
+impl Font {
+    /// Names of the bundled fonts
+    pub const BUILTIN: [&'static str; 2] = ["standard", "small"];
+
+    /// A bundled font by name
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        let s = match name {
+            "standard" => include_str!("fonts/standard.flf"),
+            "small" => include_str!("fonts/small.flf"),
+            _ => return None,
+        };
+        s.parse().ok()
+    }
+
+    /// Number of lines of a rendered line of text
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Render every line of `text`, characters missing in the font are skipped
+    #[must_use]
+    pub fn render(&self, text: &str) -> String {
+        let mut out = String::new();
+        for line in text.lines() {
+            let mut rows = vec![Vec::new(); self.height];
+            let mut prev_width = 0;
+            for c in line.chars() {
+                let c = if c == '\t' { ' ' } else { c };
+                let Some(glyph) = self.glyphs.get(&c) else {
+                    continue;
+                };
+                let width = glyph[0].len();
+                let amount = self.smush_amount(&rows, glyph, prev_width, width);
+                for (row, glyph_row) in rows.iter_mut().zip(glyph) {
+                    let start = row.len() - amount;
+                    for (k, &ch) in glyph_row.iter().enumerate() {
+                        if k < amount {
+                            let left = row[start + k];
+                            row[start + k] = self.smush(left, ch, prev_width, width).unwrap_or(ch);
+                        } else {
+                            row.push(ch);
+                        }
+                    }
+                }
+                prev_width = width;
+            }
+
+            for row in rows {
+                let row: String = row
+                    .into_iter()
+                    .map(|c| if c == self.hardblank { ' ' } else { c })
+                    .collect();
+                out.push_str(row.trim_end());
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    // How many columns the next glyph overlaps the rendered ones
+    fn smush_amount(
+        &self,
+        rows: &[Vec<char>],
+        glyph: &[Vec<char>],
+        prev_width: usize,
+        width: usize,
+    ) -> usize {
+        if self.layout & (SMUSHING | KERNING) == 0 {
+            return 0;
+        }
+        let mut amount = width;
+        for (row, glyph_row) in rows.iter().zip(glyph) {
+            let line_end = row.iter().rposition(|&c| c != ' ');
+            let glyph_start = glyph_row
+                .iter()
+                .position(|&c| c != ' ')
+                .unwrap_or(glyph_row.len());
+            let mut row_amount = glyph_start + row.len() - line_end.map_or(0, |i| i + 1);
+            if let (Some(i), Some(&right)) = (line_end, glyph_row.get(glyph_start)) {
+                if self.smush(row[i], right, prev_width, width).is_some() {
+                    row_amount += 1;
+                }
+            }
+            amount = amount.min(row_amount);
+        }
+        amount.min(rows.first().map_or(0, Vec::len))
+    }
+
+    // The character replacing `left` and `right`, if they can be smushed
+    fn smush(&self, left: char, right: char, prev_width: usize, width: usize) -> Option<char> {
+        if left == ' ' {
+            return Some(right);
+        }
+        if right == ' ' {
+            return Some(left);
+        }
+        if prev_width < 2 || width < 2 || self.layout & SMUSHING == 0 {
+            return None;
+        }
+
+        let hardblank = self.hardblank;
+        if self.layout & 63 == 0 {
+            // universal smushing
+            return Some(if right == hardblank { left } else { right });
+        }
+        if self.layout & HARDBLANK != 0 && left == hardblank && right == hardblank {
+            return Some(left);
+        }
+        if left == hardblank || right == hardblank {
+            return None;
+        }
+        if self.layout & EQUAL != 0 && left == right {
+            return Some(left);
+        }
+        if self.layout & LOWLINE != 0 {
+            let replaces = "|/\\[]{}()<>";
+            if left == '_' && replaces.contains(right) {
+                return Some(right);
+            }
+            if right == '_' && replaces.contains(left) {
+                return Some(left);
+            }
+        }
+        if self.layout & HIERARCHY != 0 {
+            let classes = ["|", "/\\", "[]", "{}", "()", "<>"];
+            let class = |c| classes.iter().position(|class| class.contains(c));
+            if let (Some(l), Some(r)) = (class(left), class(right)) {
+                if l < r {
+                    return Some(right);
+                }
+                if r < l {
+                    return Some(left);
+                }
+            }
+        }
+        if self.layout & PAIR != 0 {
+            if let ('[', ']') | (']', '[') | ('{', '}') | ('}', '{') | ('(', ')') | (')', '(') =
+                (left, right)
+            {
+                return Some('|');
+            }
+        }
+        if self.layout & BIG_X != 0 {
+            match (left, right) {
+                ('/', '\\') => return Some('|'),
+                ('\\', '/') => return Some('Y'),
+                ('>', '<') => return Some('X'),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for Font {
+    type Err = ParseFontError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let error = |line, message| ParseFontError { line, message };
+
+        let header = lines.next().map_or("", |(_, line)| line);
+        let mut header = header
+            .strip_prefix("flf2a")
+            .ok_or_else(|| error(1, "not a FIGlet font"))?
+            .chars();
+        let hardblank = header.next().ok_or_else(|| error(1, "no hardblank"))?;
+        let params = header
+            .as_str()
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<i32>, _>>()
+            .map_err(|_| error(1, "invalid header"))?;
+        if params.len() < 4 {
+            return Err(error(1, "invalid header"));
+        }
+        let height = usize::try_from(params[0])
+            .ok()
+            .filter(|&height| height > 0)
+            .ok_or_else(|| error(1, "invalid height"))?;
+        let layout = params.get(6).copied().unwrap_or(match params[3] {
+            -1 => 0,
+            0 => KERNING,
+            old_layout => SMUSHING | (old_layout & 63),
+        });
+        let comment_lines = params.get(4).copied().unwrap_or(0);
+        for _ in 0..comment_lines {
+            lines.next();
+        }
+
+        let read_glyph = |lines: &mut dyn Iterator<Item = (usize, &str)>| {
+            let mut glyph = Vec::with_capacity(height);
+            for (line, s) in lines.take(height) {
+                // the last character marks the end, it may be repeated
+                let s = s.trim_end();
+                let s = s
+                    .chars()
+                    .last()
+                    .map_or(s, |endmark| s.trim_end_matches(endmark));
+                glyph.push((line, s.chars().collect::<Vec<_>>()));
+            }
+            match glyph.len() {
+                0 => Ok(None),
+                n if n < height => Err(error(glyph[n - 1].0, "incomplete character")),
+                _ => {
+                    let width = glyph.iter().map(|(_, row)| row.len()).max().unwrap_or(0);
+                    let rows = glyph.into_iter().map(|(_, mut row)| {
+                        row.resize(width, ' ');
+                        row
+                    });
+                    Ok(Some(rows.collect::<Vec<_>>()))
+                }
+            }
+        };
+
+        let mut glyphs = BTreeMap::new();
+        for c in (' '..='~').chain(DEUTSCH.iter().copied()) {
+            match read_glyph(&mut lines)? {
+                Some(glyph) => glyphs.insert(c, glyph),
+                None => break,
+            };
+        }
+
+        // characters with a code before them
+        while let Some((line, tag)) = lines.next() {
+            if tag.trim().is_empty() {
+                continue;
+            }
+            let code = tag.split_whitespace().next().unwrap_or_default();
+            let code = parse_code(code).ok_or_else(|| error(line, "invalid character code"))?;
+            let glyph =
+                read_glyph(&mut lines)?.ok_or_else(|| error(line, "incomplete character"))?;
+            if let Some(c) = u32::try_from(code).ok().and_then(char::from_u32) {
+                glyphs.insert(c, glyph);
+            }
+        }
+
+        Ok(Self {
+            hardblank,
+            height,
+            layout,
+            glyphs,
+        })
+    }
+}
+
+// Decimal, hexadecimal with `0x` or octal with a leading `0`
+fn parse_code(s: &str) -> Option<i64> {
+    let (negative, s) = s.strip_prefix('-').map_or((false, s), |s| (true, s));
+    let code = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if s.len() > 1 && s.starts_with('0') {
+        i64::from_str_radix(&s[1..], 8).ok()?
+    } else {
+        s.parse().ok()?
+    };
+    Some(if negative { -code } else { code })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_code, Font};
+
+    // A few letters made of characters the smushing rules know
+    fn font(old_layout: i32) -> Font {
+        let mut s = format!("flf2a$ 2 2 6 {old_layout} 1\ntest font\n");
+        for c in (' '..='~').chain("ÄÖÜäöüß".chars()) {
+            let glyph = match c {
+                ' ' => "$$@\n$$@@\n",
+                'A' => " /\\ @\n/--\\@@\n",
+                'B' => "|_ @\n|_)@@\n",
+                'L' => "|  @\n|__@@\n",
+                _ => "@\n@@\n",
+            };
+            s.push_str(glyph);
+        }
+        s.push_str("0x263A smiley\n:)@\n  @@\n");
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn layouts() {
+        // full width
+        assert_eq!(font(-1).render("AB"), " /\\ |_\n/--\\|_)\n");
+        // kerning, the glyphs touch
+        assert_eq!(font(0).render("LA"), "|   /\\\n|__/--\\\n");
+        // smushing with the underscore rule
+        assert_eq!(font(2).render("LA"), "|  /\\\n|_/--\\\n");
+        // smushing with the hierarchy rule, `\\` replaces `|`
+        assert_eq!(font(4).render("AL"), " /\\|\n/--\\__\n");
+        // universal smushing, the right character wins
+        assert_eq!(font(128).render("AL"), " /\\|\n/--|__\n");
+        assert_eq!(font(128).render("LA"), "|  /\\\n|_/--\\\n");
+
+        assert_eq!(font(-1).render("A B"), " /\\   |_\n/--\\  |_)\n");
+        assert_eq!(font(-1).render("☺\nL"), ":)\n\n|\n|__\n");
+        assert_eq!(font(-1).render("ÄA"), " /\\\n/--\\\n");
+    }
+
+    #[test]
+    fn builtin() {
+        for name in Font::BUILTIN {
+            let font = Font::builtin(name).unwrap();
+            let banner = font.render("Lolcrab 0.4!");
+            assert_eq!(banner.lines().count(), font.height());
+            assert!(banner.lines().any(|line| line.chars().count() > 20));
+        }
+        assert!(Font::builtin("block").is_none());
+
+        // the same as the output of `figlet`, without trailing spaces
+        let standard = Font::builtin("standard").unwrap();
+        assert_eq!(
+            standard.render("Hello"),
+            concat!(
+                "  _   _      _ _\n",
+                " | | | | ___| | | ___\n",
+                " | |_| |/ _ \\ | |/ _ \\\n",
+                " |  _  |  __/ | | (_) |\n",
+                " |_| |_|\\___|_|_|\\___/\n",
+                "\n",
+            )
+        );
+        let small = Font::builtin("small").unwrap();
+        assert_eq!(
+            small.render("Hello"),
+            concat!(
+                " _  _     _ _\n",
+                "| || |___| | |___\n",
+                "| __ / -_) | / _ \\\n",
+                "|_||_\\___|_|_\\___/\n",
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s: &str| s.parse::<Font>().unwrap_err().to_string();
+        assert_eq!(
+            err("hello"),
+            "invalid FIGlet font: not a FIGlet font (line 1)"
+        );
+        assert_eq!(
+            err("flf2a$ 0 0 1 -1"),
+            "invalid FIGlet font: invalid height (line 1)"
+        );
+        assert_eq!(
+            err("flf2a$ 2 2 1 -1 0\n@\n@@\n@\n"),
+            "invalid FIGlet font: incomplete character (line 4)"
+        );
+        assert_eq!(parse_code("0x41"), Some(65));
+        assert_eq!(parse_code("0101"), Some(65));
+        assert_eq!(parse_code("-65"), Some(-65));
+        assert_eq!(parse_code("x"), None);
+    }
+}
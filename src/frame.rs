@@ -0,0 +1,184 @@
+use bstr::ByteSlice;
+use unicode_width::UnicodeWidthChar;
+
+use crate::Lolcrab;
+
+/// Characters of the border around a [`Frame`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderStyle {
+    /// `┌─┐`
+    #[default]
+    Single,
+    /// `╔═╗`
+    Double,
+    /// `╭─╮`
+    Rounded,
+    /// `┏━┓`
+    Heavy,
+    /// `+-+`
+    Ascii,
+}
+
+impl BorderStyle {
+    // top left, top right, bottom left, bottom right, horizontal, vertical
+    const fn chars(self) -> [char; 6] {
+        match self {
+            Self::Single => ['┌', '┐', '└', '┘', '─', '│'],
+            Self::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+            Self::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            Self::Heavy => ['┏', '┓', '┗', '┛', '━', '┃'],
+            Self::Ascii => ['+', '+', '+', '+', '-', '|'],
+        }
+    }
+}
+
+/// A border drawn around text by [`Lolcrab::frame`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Frame {
+    /// Border characters
+    pub style: BorderStyle,
+    /// Empty columns left and right, and empty lines above and below the text
+    pub padding: usize,
+    /// Title in the top border
+    pub title: Option<String>,
+}
+
+impl Lolcrab {
+    /// Draw a border around `text`, to be colorized like any other text, so
+    /// the border gets the colors at its position.
+    ///
+    /// Widths are measured like the colorize functions do, tabs are expanded
+    /// and escape sequences take no space.
+    ///
+    /// ```
+    /// use lolcrab::{BorderStyle, Frame, Lolcrab};
+    ///
+    /// let lol = Lolcrab::new(None, None);
+    /// let frame = Frame {
+    ///     style: BorderStyle::Rounded,
+    ///     padding: 0,
+    ///     title: Some("Hi".to_string()),
+    /// };
+    /// let text = lol.frame("Hello\nWorld!\n", &frame);
+    /// assert_eq!(text, "╭─ Hi ─╮\n│Hello │\n│World!│\n╰──────╯\n");
+    /// ```
+    #[must_use]
+    pub fn frame(&self, text: &str, frame: &Frame) -> String {
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] =
+            frame.style.chars();
+        let lines: Vec<_> = ByteSlice::lines(text.as_bytes())
+            .map(|line| self.expand_line(line))
+            .collect();
+        let title = frame.title.as_ref().map(|title| {
+            let title = title.replace(['\r', '\n'], " ");
+            self.expand_line(title.as_bytes())
+        });
+
+        let padding = " ".repeat(frame.padding);
+        let text_width = lines.iter().map(|(_, width)| *width).max().unwrap_or(0);
+        // the title needs a space and a border character on both sides
+        let width = (text_width + 2 * frame.padding).max(title.as_ref().map_or(0, |t| t.1 + 4));
+        let border = |n: usize| horizontal.to_string().repeat(n);
+
+        let mut out = String::new();
+        out.push(top_left);
+        match &title {
+            Some((title, title_width)) => {
+                out.push(horizontal);
+                out.push(' ');
+                out.push_str(title);
+                out.push(' ');
+                out.push_str(&border(width - title_width - 3));
+            }
+            None => out.push_str(&border(width)),
+        }
+        out.push(top_right);
+        out.push('\n');
+
+        let empty = (String::new(), 0);
+        let padding_lines = (0..frame.padding).map(|_| &empty);
+        for (line, line_width) in padding_lines.clone().chain(&lines).chain(padding_lines) {
+            out.push(vertical);
+            out.push_str(&padding);
+            out.push_str(line);
+            out.push_str(&" ".repeat(width - frame.padding - line_width));
+            out.push(vertical);
+            out.push('\n');
+        }
+
+        out.push(bottom_left);
+        out.push_str(&border(width));
+        out.push(bottom_right);
+        out.push('\n');
+        out
+    }
+
+    // The line with tabs expanded, and its display width
+    fn expand_line(&self, line: &[u8]) -> (String, usize) {
+        let mut expanded = String::new();
+        let mut width = 0;
+        let mut escaping = false;
+        for g in line.graphemes() {
+            if g == "\x1B" {
+                escaping = true;
+            } else if escaping {
+                escaping = g.len() != 1 || !g.as_bytes()[0].is_ascii_alphabetic();
+            } else if g == "\t" {
+                let n = self.tab_width as usize - width % self.tab_width as usize;
+                expanded.push_str(&" ".repeat(n));
+                width += n;
+                continue;
+            } else {
+                width += g
+                    .chars()
+                    .next()
+                    .and_then(UnicodeWidthChar::width)
+                    .unwrap_or(0);
+            }
+            expanded.push_str(g);
+        }
+        (expanded, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BorderStyle, Frame};
+    use crate::Lolcrab;
+
+    #[test]
+    fn frame() {
+        let lol = Lolcrab::new(None, None);
+        let frame = |style, padding, title: Option<&str>| Frame {
+            style,
+            padding,
+            title: title.map(String::from),
+        };
+
+        assert_eq!(
+            lol.frame("ab\n", &frame(BorderStyle::Single, 0, None)),
+            "┌──┐\n│ab│\n└──┘\n"
+        );
+        assert_eq!(
+            lol.frame("ab\r\nc", &frame(BorderStyle::Double, 1, None)),
+            "╔════╗\n║    ║\n║ ab ║\n║ c  ║\n║    ║\n╚════╝\n"
+        );
+        assert_eq!(
+            lol.frame("", &frame(BorderStyle::Heavy, 0, Some("x"))),
+            "┏━ x ━┓\n┗━━━━━┛\n"
+        );
+        assert_eq!(
+            lol.frame("a long line\n", &frame(BorderStyle::Ascii, 0, Some("x"))),
+            "+- x -------+\n|a long line|\n+-----------+\n"
+        );
+
+        // wide characters, tabs and escape sequences
+        assert_eq!(
+            lol.frame(
+                "蟹\na\tb\n\x1B[1mc\x1B[0m\n",
+                &frame(BorderStyle::Rounded, 0, None)
+            ),
+            "╭─────╮\n│蟹   │\n│a   b│\n│\x1B[1mc\x1B[0m    │\n╰─────╯\n"
+        );
+    }
+}
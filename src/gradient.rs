@@ -0,0 +1,188 @@
+use std::error;
+use std::f32::consts::{FRAC_PI_3, PI};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use colorgrad::Color;
+
+const PI2_3: f32 = PI * 2.0 / 3.0;
+
+#[derive(Clone)]
+struct LolcatGradient {}
+
+impl colorgrad::Gradient for LolcatGradient {
+    fn at(&self, t: f32) -> Color {
+        let t = (0.5 - t) * PI;
+        Color::new(
+            (t + FRAC_PI_3).sin().powi(2).clamp(0.0, 1.0),
+            t.sin().powi(2).clamp(0.0, 1.0),
+            (t + PI2_3).sin().powi(2).clamp(0.0, 1.0),
+            1.0,
+        )
+    }
+}
+
+// Returns the color at the nearest end outside of the domain, where the
+// inner gradient may not
+#[derive(Clone)]
+pub(crate) struct ClampedGradient<G>(pub(crate) G);
+
+impl<G: colorgrad::Gradient + Clone> colorgrad::Gradient for ClampedGradient<G> {
+    fn at(&self, t: f32) -> Color {
+        let (dmin, dmax) = self.0.domain();
+        self.0.at(t.clamp(dmin, dmax))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        self.0.domain()
+    }
+}
+
+// Part of a gradient from `start` to `end` (0..=1), reversed if `start > end`
+#[derive(Clone)]
+pub(crate) struct SubGradient {
+    pub(crate) inner: Arc<dyn colorgrad::Gradient + Send + Sync>,
+    pub(crate) start: f32,
+    pub(crate) end: f32,
+}
+
+impl colorgrad::Gradient for SubGradient {
+    fn at(&self, t: f32) -> Color {
+        let (dmin, dmax) = self.inner.domain();
+        // noise mode samples a bit outside of 0..1, stay inside the part
+        let t = self.start + t.clamp(0.0, 1.0) * (self.end - self.start);
+        self.inner.at(dmin + t * (dmax - dmin))
+    }
+}
+
+// Generates the `Gradient` enum with its list of names and constructors from
+// the single list in `presets.rs`. colorgrad can't list its presets, the list
+// has to follow `colorgrad::preset` when colorgrad is updated.
+macro_rules! presets {
+    ($($variant:ident $name:literal => $gradient:expr,)*) => {
+        /// Preset gradients
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+        pub enum Gradient {
+            $(
+                #[cfg_attr(feature = "cli", value(name = $name))]
+                $variant,
+            )*
+        }
+
+        impl Gradient {
+            /// All preset gradients
+            pub const ALL: [Self; [$($name),*].len()] = [$(Self::$variant),*];
+
+            /// Name of the preset, as used by the CLI
+            #[must_use]
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            #[must_use]
+            pub fn to_gradient(self) -> Box<dyn colorgrad::Gradient + Send + Sync> {
+                match self {
+                    $(Self::$variant => Box::new($gradient),)*
+                }
+            }
+
+            // The expression building the gradient, to check the names
+            #[cfg(test)]
+            fn source(self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($gradient),)*
+                }
+            }
+        }
+    };
+}
+
+include!("presets.rs");
+
+fn build_gradient(colors: &[&str]) -> colorgrad::CatmullRomGradient {
+    colorgrad::GradientBuilder::new()
+        .html_colors(colors)
+        .mode(colorgrad::BlendMode::Oklab)
+        .build()
+        .unwrap()
+}
+
+impl fmt::Display for Gradient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Gradient {
+    type Err = ParseGradientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|g| g.name().eq_ignore_ascii_case(&s.replace('_', "-")))
+            .ok_or_else(|| ParseGradientError {
+                name: s.to_string(),
+            })
+    }
+}
+
+/// Error returned when parsing an unknown preset name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGradientError {
+    name: String,
+}
+
+impl fmt::Display for ParseGradientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown gradient '{}'", self.name)
+    }
+}
+
+impl error::Error for ParseGradientError {}
+
+#[cfg(test)]
+mod tests {
+    use super::Gradient;
+
+    #[test]
+    fn names() {
+        for &g in &Gradient::ALL {
+            assert_eq!(g.name().parse(), Ok(g));
+        }
+        assert_eq!("rd_yl_gn".parse(), Ok(Gradient::RdYlGn));
+        assert!("foo".parse::<Gradient>().is_err());
+
+        #[cfg(feature = "cli")]
+        {
+            use clap::ValueEnum;
+            let names: Vec<_> = Gradient::value_variants()
+                .iter()
+                .map(|g| g.to_possible_value().unwrap().get_name().to_string())
+                .collect();
+            assert_eq!(names, Gradient::ALL.map(|g| g.name()));
+        }
+    }
+
+    // The names of colorgrad's presets are their function names
+    #[test]
+    fn colorgrad_presets() {
+        let mut upstream = 0;
+        for g in Gradient::ALL {
+            let source: String = g.source().split_whitespace().collect();
+            let Some(function) = source.strip_prefix("colorgrad::preset::") else {
+                continue;
+            };
+            let function = function.trim_end_matches("()");
+            let function = function.trim_end_matches("_default");
+            assert_eq!(function.replace('_', "-"), g.name());
+            upstream += 1;
+        }
+        // all but our own
+        assert_eq!(upstream, Gradient::ALL.len() - 2);
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+
+use lolcrab::{Config, CustomGradient, GradientSpec};
+
+use crate::config_file::{toml_error, Error};
+
+/// Read a gradient file, the format depends on the extension:
+///
+/// * `.ggr` GIMP gradient
+/// * `.json` and `.toml` color stops, like the `[gradients.NAME]` tables of the config file
+/// * anything else is a gradient in CSS gradient format
+pub fn read(path: &Path) -> Result<GradientSpec, Error> {
+    let s = fs::read_to_string(path).map_err(|e| Error::Io(path.to_path_buf(), e))?;
+    parse(path, &s)
+}
+
+fn parse(path: &Path, s: &str) -> Result<GradientSpec, Error> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+
+    let spec = match extension.as_deref() {
+        Some("ggr") => GradientSpec::Ggr(s.to_string()),
+        Some("json") => {
+            let custom: CustomGradient =
+                serde_json::from_str(s).map_err(|e| json_error(path, &e))?;
+            GradientSpec::Custom(custom)
+        }
+        Some("toml") => {
+            let custom: CustomGradient = toml::from_str(s).map_err(|e| toml_error(path, s, &e))?;
+            GradientSpec::Custom(custom)
+        }
+        _ => GradientSpec::Css(s.split_whitespace().collect::<Vec<_>>().join(" ")),
+    };
+
+    // report invalid colors now, with the file name
+    let config = Config {
+        gradient: spec,
+        ..Config::default()
+    };
+    match config.build() {
+        Ok(_) => Ok(config.gradient),
+        Err(e) => Err(Error::Parse {
+            path: path.to_path_buf(),
+            line: None,
+            column: None,
+            message: e.to_string(),
+        }),
+    }
+}
+
+fn json_error(path: &Path, e: &serde_json::Error) -> Error {
+    // the location is also part of the message
+    let message = e.to_string();
+    let message = message
+        .find(" at line ")
+        .map_or(message.as_str(), |i| &message[..i]);
+    Error::Parse {
+        path: path.to_path_buf(),
+        line: Some(e.line()),
+        column: Some(e.column()),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lolcrab::{BlendMode, GradientSpec};
+
+    use super::parse;
+
+    const GGR: &str = "GIMP Gradient
+Name: Test
+2
+0.0 0.25 0.5 1.0 0.0 0.0 1.0 1.0 1.0 0.0 1.0 0 0
+0.5 0.75 1.0 1.0 1.0 0.0 1.0 0.0 0.0 1.0 1.0 0 0
+";
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            parse(Path::new("a.ggr"), GGR).unwrap(),
+            GradientSpec::Ggr(GGR.to_string())
+        );
+
+        let json = r##"{ "colors": ["#f00", "gold"], "blend-mode": "rgb" }"##;
+        let Ok(GradientSpec::Custom(custom)) = parse(Path::new("a.JSON"), json) else {
+            panic!("not a custom gradient");
+        };
+        assert_eq!(custom.colors, ["#f00", "gold"]);
+        assert_eq!(custom.blend_mode, BlendMode::Rgb);
+
+        let toml = "colors = ['#f00', 'gold']\npositions = [0, 0.7]\n";
+        let Ok(GradientSpec::Custom(custom)) = parse(Path::new("a.toml"), toml) else {
+            panic!("not a custom gradient");
+        };
+        assert_eq!(custom.positions, [0.0, 0.7]);
+
+        assert_eq!(
+            parse(Path::new("a.css"), "#f00,\n  gold 30%,\n  #00f\n").unwrap(),
+            GradientSpec::Css("#f00, gold 30%, #00f".to_string())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |path: &str, s: &str| parse(Path::new(path), s).unwrap_err().to_string();
+
+        assert_eq!(
+            err("a.ggr", "GIMP Gradient\nName: Test\n1\n0.0 0.5\n"),
+            "a.ggr: invalid GIMP gradient: invalid segment (line 4)"
+        );
+        assert_eq!(
+            err("a.json", "{\n  \"colors\": 1\n}"),
+            "a.json:2:13: invalid type: integer `1`, expected a sequence"
+        );
+        assert_eq!(
+            err("a.toml", "colors = ['#f00', 'nope']\n"),
+            "a.toml: invalid gradient: invalid html colors: 'nope'"
+        );
+        assert_eq!(
+            err("a.css", "#f00, nope"),
+            "a.css: invalid gradient: invalid css gradient"
+        );
+    }
+}
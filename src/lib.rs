@@ -10,25 +10,74 @@
 //! lolcrab = { version = "0.4", default-features = "false" }
 //! ```
 //!
+//! ## Optional Features
+//!
+//! * `async` — async versions of the colorize functions for `tokio`'s `AsyncBufRead` and `AsyncWrite`
+//! * `banner` — [`Font`], render text in large letters using FIGlet fonts
+//! * `columns` — `Lolcrab::colorize_columns`, every column of tabular input in its own color
+//! * `rayon` — `Lolcrab::colorize_read_par`, colorize large inputs using multiple threads
+//! * `serde` — `Serialize` and `Deserialize` for [`Config`]
+//!
 
-use std::f32::consts::TAU;
+use std::fmt;
 use std::io::{prelude::*, Write};
+use std::sync::Arc;
 use std::{thread, time};
 
 use bstr::{io::BufReadExt, ByteSlice};
 use colorgrad::Color;
-use noise::NoiseFn;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
-#[cfg(feature = "cli")]
-use std::process;
+#[cfg(feature = "async")]
+mod async_io;
+
+#[cfg(feature = "banner")]
+mod figlet;
 
 #[cfg(feature = "cli")]
 mod cli;
 
+#[cfg(feature = "columns")]
+mod columns;
+
+#[cfg(feature = "rayon")]
+mod parallel;
+
+mod ansi;
+mod builder;
+mod config;
+mod frame;
+mod gradient;
+mod lut;
+mod palette;
+mod stream;
+mod terminal;
+mod writer;
+
+use ansi::Sgr;
+use lut::{Lut, Rgb};
+use stream::Stream;
+
+pub use builder::{BuildError, LolcrabBuilder, Settings};
+pub use config::{
+    AnimationConfig, BlendMode, Config, ConfigError, Continuity, CustomGradient, Description,
+    GradientConfig, GradientSpec, Interpolation, LinearConfig, Mode, NoiseConfig, NoiseType,
+};
+pub use frame::{BorderStyle, Frame};
+pub use gradient::{Gradient, ParseGradientError};
+pub use palette::RandomScheme;
+pub use terminal::{restore_terminal, TerminalGuard};
+pub use writer::LolcrabWriter;
+
+#[cfg(feature = "banner")]
+pub use figlet::{Font, ParseFontError};
+
+#[cfg(feature = "columns")]
+pub use columns::Columns;
+
 #[cfg(feature = "cli")]
-pub use cli::{Gradient, Opt};
+pub use cli::Opt;
 
 /// # Example
 ///
@@ -51,9 +100,10 @@ pub use cli::{Gradient, Opt};
 /// # Ok(())
 /// # }
 /// ```
+#[derive(Clone)]
 pub struct Lolcrab {
-    pub gradient: Box<dyn colorgrad::Gradient>,
-    pub noise: Box<dyn noise::NoiseFn<f64, 2>>,
+    pub gradient: Arc<dyn colorgrad::Gradient + Send + Sync>,
+    pub noise: Arc<dyn noise::NoiseFn<f64, 2> + Send + Sync>,
     noise_scale: f64,
     invert: bool,
     tab_width: isize,
@@ -64,24 +114,70 @@ pub struct Lolcrab {
     shift_x: f32,
     shift_y: f32,
     offset: f32,
+    // in degrees
     angle: f32,
-    distance: f32,
+    spread: f32,
 
     anim_duration: usize,
     anim_sleep: time::Duration,
+
+    lut_size: usize,
+    lut: Option<Arc<Lut>>,
+
+    compress: bool,
+    tolerance: f32,
+    // last written color, and the same in Oklab
+    last_color: Option<(Rgb, [f32; 3])>,
+
+    paragraph: Continuity,
+    // no visible characters in the current line yet
+    blank_line: bool,
+
+    seed: Option<u64>,
+    rng: fastrand::Rng,
+
+    // gradient position of the current column, instead of the field
+    column: Option<f32>,
+
+    // what `describe` knows, with the gradient and noise it applies to
+    gradient_config: Option<(
+        Arc<GradientConfig>,
+        Arc<dyn colorgrad::Gradient + Send + Sync>,
+    )>,
+    builtin_noise: Option<Arc<dyn noise::NoiseFn<f64, 2> + Send + Sync>>,
 }
 
 impl Lolcrab {
     #[must_use]
     pub fn new(
-        gradient: Option<Box<dyn colorgrad::Gradient>>,
-        ns: Option<Box<dyn noise::NoiseFn<f64, 2>>>,
+        gradient: Option<Box<dyn colorgrad::Gradient + Send + Sync>>,
+        ns: Option<Box<dyn noise::NoiseFn<f64, 2> + Send + Sync>>,
     ) -> Self {
-        let angle = fastrand::f32() * TAU;
-        let distance = 0.017;
-        Self {
-            gradient: gradient.unwrap_or(Box::new(colorgrad::preset::rainbow())),
-            noise: ns.unwrap_or(Box::new(noise::OpenSimplex::new(fastrand::u32(..)))),
+        Self::init(gradient, ns, None)
+    }
+
+    /// Like [`Lolcrab::new`] with the default gradient and noise, but the noise,
+    /// angle, offset and [`Lolcrab::randomize_position`] only depend on `seed`
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::init(None, None, Some(seed))
+    }
+
+    pub(crate) fn init(
+        gradient: Option<Box<dyn colorgrad::Gradient + Send + Sync>>,
+        ns: Option<Box<dyn noise::NoiseFn<f64, 2> + Send + Sync>>,
+        seed: Option<u64>,
+    ) -> Self {
+        let mut rng = seed.map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed);
+        let angle = rng.f32() * 360.0;
+        let builtin_noise = ns.is_none();
+        let mut lol = Self {
+            gradient: gradient
+                .map_or_else(|| Arc::new(colorgrad::preset::rainbow()) as _, Arc::from),
+            noise: ns.map_or_else(
+                || Arc::new(noise::OpenSimplex::new(rng.u32(..))) as _,
+                Arc::from,
+            ),
             noise_scale: 0.034,
             invert: false,
             tab_width: 4,
@@ -89,15 +185,37 @@ impl Lolcrab {
             y: 0,
 
             linear: false,
-            shift_x: angle.cos() * distance / 2.0,
-            shift_y: angle.sin() * distance,
-            offset: fastrand::f32(),
+            shift_x: 0.0,
+            shift_y: 0.0,
+            offset: rng.f32(),
             angle,
-            distance,
+            spread: 13.0,
 
             anim_duration: 5,
             anim_sleep: time::Duration::from_millis(150),
+
+            lut_size: 1024,
+            lut: None,
+
+            compress: false,
+            tolerance: 0.0,
+            last_color: None,
+
+            paragraph: Continuity::Continue,
+            blank_line: true,
+
+            seed,
+            rng,
+            column: None,
+
+            gradient_config: None,
+            builtin_noise: None,
+        };
+        if builtin_noise {
+            lol.builtin_noise = Some(Arc::clone(&lol.noise));
         }
+        lol.calc_shift();
+        lol
     }
 
     /// Noise scale. Try value between 0.01 .. 0.2
@@ -125,25 +243,45 @@ impl Lolcrab {
         self.anim_duration = duration.clamp(1, 30);
     }
 
+    /// Number of precomputed gradient colors (default: 1024).
+    /// Set to 0 to sample the gradient for every character.
+    pub fn set_lut_size(&mut self, size: usize) {
+        self.lut_size = size;
+    }
+
+    /// Only write a color when it differs from the previous one,
+    /// and don't write colors for whitespace (unless in invert mode)
+    pub fn set_compress(&mut self, compress: bool) {
+        self.compress = compress;
+    }
+
+    /// Colors closer than this (Oklab ΔE, try 0.01..0.05) to the previous
+    /// color are not written. Only used with `set_compress(true)`.
+    pub fn set_color_tolerance(&mut self, delta_e: f32) {
+        self.tolerance = delta_e.max(0.0);
+    }
+
     /// Linear mode
     pub fn set_linear(&mut self, b: bool) {
         self.linear = b;
     }
 
     fn calc_shift(&mut self) {
-        self.shift_x = self.angle.cos() * self.distance / 2.0;
-        self.shift_y = self.angle.sin() * self.distance;
+        let angle = self.angle.to_radians();
+        let distance = remap(self.spread, 0.0, 100.0, 0.005, 0.1);
+        self.shift_x = angle.cos() * distance / 2.0;
+        self.shift_y = angle.sin() * distance;
     }
 
     /// Sets angle in degrees (0..360)
     pub fn set_angle(&mut self, angle: f32) {
-        self.angle = angle.to_radians();
+        self.angle = angle;
         self.calc_shift();
     }
 
     /// Sets spread (0..100)
-    pub fn set_spread(&mut self, distance: f32) {
-        self.distance = remap(distance, 0.0, 100.0, 0.005, 0.1);
+    pub fn set_spread(&mut self, spread: f32) {
+        self.spread = spread;
         self.calc_shift();
     }
 
@@ -176,20 +314,76 @@ impl Lolcrab {
     /// Randomize noise position
     pub fn randomize_position(&mut self) {
         self.x = 0;
-        self.y = fastrand::isize(-999_999..999_999);
+        self.y = self.rng.isize(-999_999..999_999);
+    }
+
+    /// Reset or randomize the position, as at the start of a new file
+    pub fn apply_continuity(&mut self, continuity: Continuity) {
+        match continuity {
+            Continuity::Continue => {}
+            Continuity::Reset => self.reset_position(),
+            Continuity::Randomize => self.randomize_position(),
+        }
+    }
+
+    /// What happens at a paragraph, after one or more blank lines (default: continue)
+    pub fn set_paragraph_continuity(&mut self, continuity: Continuity) {
+        self.paragraph = continuity;
+    }
+
+    // Next line, a blank line ends a paragraph
+    fn end_line(&mut self) {
+        self.reset_col();
+        self.step_row(1);
+        if self.blank_line {
+            self.apply_continuity(self.paragraph);
+        }
+        self.blank_line = true;
     }
 
     #[doc(hidden)]
     pub fn get_color(&mut self) -> Color {
+        self.gradient.at(self.position())
+    }
+
+    // Gradient position for the current column and row
+    fn position(&self) -> f32 {
+        if let Some(t) = self.column {
+            return t;
+        }
         if self.linear {
             let t = self.offset + self.x as f32 * self.shift_x + self.y as f32 * self.shift_y;
-            return self.gradient.at(modulo(t, 1.0));
+            return modulo(t, 1.0);
         }
         let position = self.noise.get([
             self.x as f64 * self.noise_scale,
             self.y as f64 * self.noise_scale * 2.0,
         ]) as f32;
-        self.gradient.at(remap(position, -0.5, 0.5, -0.1, 1.1))
+        remap(position, -0.5, 0.5, -0.1, 1.1)
+    }
+
+    // Like `get_color`, but uses the lookup table. Also returns the text color for invert mode.
+    fn get_colors(&mut self) -> (Rgb, Rgb) {
+        let t = self.position();
+        if self.lut_size > 1 {
+            if let Some(c) = self.lut().get(t) {
+                return c;
+            }
+        }
+        lut::colors(&self.gradient.at(t))
+    }
+
+    // Lookup table for the current gradient, (re)built when needed
+    fn lut(&mut self) -> &Lut {
+        let (gradient, size) = (&self.gradient, self.lut_size);
+        if !self
+            .lut
+            .as_ref()
+            .is_some_and(|lut| lut.is_for(gradient, size))
+        {
+            self.lut = Some(Arc::new(Lut::new(gradient, size)));
+        }
+        self.lut.as_ref().unwrap()
     }
 
     #[inline]
@@ -201,14 +395,16 @@ impl Lolcrab {
     ) -> std::io::Result<bool> {
         let mut escaping = escaping;
         if grapheme == "\x1B" {
+            // the escape sequence may change the colors
+            self.last_color = None;
             out.write_all(b"\x1B")?;
             return Ok(true);
         }
 
         if grapheme == "\n" || grapheme == "\r\n" {
-            self.reset_col();
-            self.step_row(1);
+            self.end_line();
             if self.invert {
+                self.last_color = None;
                 out.write_all(b"\x1B[49m")?;
             }
             out.write_all(grapheme.as_bytes())?;
@@ -222,24 +418,21 @@ impl Lolcrab {
                 !c.is_ascii_lowercase() && !c.is_ascii_uppercase()
             };
         } else {
-            let col = self.get_color();
-            let [r, g, b, _] = col.to_rgba8();
-
-            if self.invert {
-                let lum = color_luminance(&col);
-                let eps = 0.013;
-
-                let v = if lum < eps {
-                    remap(lum, eps, 0.0, 0.22, 0.2)
-                } else {
-                    remap(lum, eps, 1.0, 0.0, 0.7)
-                };
-                let [x, y, z, _] = Color::new(v, v, v, 1.0).to_rgba8();
-
-                write!(out, "\x1B[48;2;{r};{g};{b};38;2;{x};{y};{z}m{grapheme}")?;
-            } else {
-                write!(out, "\x1B[38;2;{r};{g};{b}m{grapheme}")?;
+            // the foreground color of whitespace isn't visible
+            let whitespace = grapheme.chars().all(char::is_whitespace);
+            self.blank_line &= whitespace;
+            let skip = self.compress && !self.invert && whitespace;
+            if !skip {
+                let (col, fg) = self.get_colors();
+                if self.is_new_color(col) {
+                    if self.invert {
+                        out.write_all(Sgr::bg_fg(col, fg).as_bytes())?;
+                    } else {
+                        out.write_all(Sgr::fg(col).as_bytes())?;
+                    }
+                }
             }
+            out.write_all(grapheme.as_bytes())?;
 
             self.step_col(
                 grapheme
@@ -252,8 +445,62 @@ impl Lolcrab {
         Ok(escaping)
     }
 
-    // TODO
-    fn colorize_anim(&mut self, text: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+    // Like `handle_grapheme`, but expands tabs
+    fn colorize_grapheme(
+        &mut self,
+        out: &mut impl Write,
+        grapheme: &str,
+        escaping: bool,
+    ) -> std::io::Result<bool> {
+        if grapheme != "\t" {
+            return self.handle_grapheme(out, grapheme, escaping);
+        }
+        let n = self.tab_width - self.x % self.tab_width;
+        if self.invert {
+            let mut escaping = escaping;
+            for _ in 0..n {
+                escaping = self.handle_grapheme(out, " ", escaping)?;
+            }
+            Ok(escaping)
+        } else {
+            self.step_col(n);
+            out.write_all(" ".repeat(n as usize).as_bytes())?;
+            Ok(escaping)
+        }
+    }
+
+    // Whether `col` has to be written, remembers it if so
+    fn is_new_color(&mut self, col: Rgb) -> bool {
+        if !self.compress {
+            return true;
+        }
+        let lab = if self.tolerance > 0.0 {
+            let [l, a, b, _] = Color::from_rgba8(col[0], col[1], col[2], 255).to_oklaba();
+            [l, a, b]
+        } else {
+            [0.0; 3]
+        };
+        if let Some((last, last_lab)) = self.last_color {
+            if last == col || self.tolerance > 0.0 && distance(&last_lab, &lab) <= self.tolerance {
+                return false;
+            }
+        }
+        self.last_color = Some((col, lab));
+        true
+    }
+
+    // Restore the default foreground (and background) color
+    fn write_reset(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        self.last_color = None;
+        if self.invert {
+            out.write_all(b"\x1B[39;49m")
+        } else {
+            out.write_all(b"\x1B[39m")
+        }
+    }
+
+    // Display width of a single line, with tabs expanded
+    fn text_len(&self, text: &[u8]) -> isize {
         let mut text_len: isize = 0;
         for g in text.graphemes() {
             if g == "\t" {
@@ -266,15 +513,66 @@ impl Lolcrab {
                     .unwrap_or(0) as isize;
             }
         }
-        self.x = -(self.anim_duration as isize - 1) * text_len;
+        text_len
+    }
+
+    /// Linear mode with exactly one pass of the gradient across a block of
+    /// `width` columns and `height` lines, along the angle. Resets the position.
+    pub fn fit(&mut self, width: usize, height: usize) {
+        self.linear = true;
+        self.reset_position();
+
+        let angle = self.angle.to_radians();
+        let (dx, dy) = (angle.cos() / 2.0, angle.sin());
+        let (w, h) = (
+            width.saturating_sub(1) as f32,
+            height.saturating_sub(1) as f32,
+        );
+        // position of the corners along the angle
+        let corners = [0.0, dx * w, dy * h, dx * w + dy * h];
+        let min = corners.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = corners.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        // just below 1, so the last corner doesn't wrap around to the first color
+        let scale = if max > min { 0.9999 / (max - min) } else { 0.0 };
+        self.shift_x = dx * scale;
+        self.shift_y = dy * scale;
+        self.offset = -min * scale;
+    }
+
+    /// Linear mode with one pass of the gradient every `width` columns, the same
+    /// on every line. A line wrapped by the terminal starts with the first color
+    /// again. Resets the position, the angle and the offset, set another offset
+    /// afterwards to start with another color.
+    pub fn stretch(&mut self, width: usize) {
+        self.linear = true;
+        self.reset_position();
+        self.angle = 0.0;
+        self.shift_x = if width > 0 { 1.0 / width as f32 } else { 0.0 };
+        self.shift_y = 0.0;
+        self.offset = 0.0;
+    }
+
+    /// Like [`Lolcrab::fit`], with the width of the widest line and the number of lines of `text`
+    pub fn fit_text(&mut self, text: &[u8]) {
+        let width = ByteSlice::lines(text)
+            .map(|line| self.text_len(line))
+            .max()
+            .unwrap_or(0);
+        let height = ByteSlice::lines(text).count();
+        self.fit(width as usize, height);
+    }
+
+    // TODO
+    fn colorize_anim(&mut self, text: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+        self.x = -(self.anim_duration as isize - 1) * self.text_len(text);
         for _ in 0..self.anim_duration {
             out.write_all(b"\x1B[0G")?;
             self.colorize(text, out)?;
             thread::sleep(self.anim_sleep);
         }
         out.write_all(b"\n")?;
-        self.reset_col();
-        self.step_row(1);
+        self.end_line();
         out.flush()
     }
 
@@ -284,26 +582,9 @@ impl Lolcrab {
     pub fn colorize(&mut self, text: &[u8], out: &mut impl Write) -> std::io::Result<()> {
         let mut escaping = false;
         for grapheme in text.graphemes() {
-            if grapheme == "\t" {
-                let n = self.tab_width - self.x % self.tab_width;
-                if self.invert {
-                    for _ in 0..n {
-                        escaping = self.handle_grapheme(out, " ", escaping)?;
-                    }
-                } else {
-                    self.step_col(n);
-                    out.write_all(" ".repeat(n as usize).as_bytes())?;
-                }
-            } else {
-                escaping = self.handle_grapheme(out, grapheme, escaping)?;
-            }
-        }
-
-        if self.invert {
-            out.write_all(b"\x1B[39;49m")?;
-        } else {
-            out.write_all(b"\x1B[39m")?;
+            escaping = self.colorize_grapheme(out, grapheme, escaping)?;
         }
+        self.write_reset(out)?;
         out.flush()
     }
 
@@ -313,26 +594,9 @@ impl Lolcrab {
     pub fn colorize_str(&mut self, text: &str, out: &mut impl Write) -> std::io::Result<()> {
         let mut escaping = false;
         for grapheme in UnicodeSegmentation::graphemes(text, true) {
-            if grapheme == "\t" {
-                let n = self.tab_width - self.x % self.tab_width;
-                if self.invert {
-                    for _ in 0..n {
-                        escaping = self.handle_grapheme(out, " ", escaping)?;
-                    }
-                } else {
-                    self.step_col(n);
-                    out.write_all(" ".repeat(n as usize).as_bytes())?;
-                }
-            } else {
-                escaping = self.handle_grapheme(out, grapheme, escaping)?;
-            }
-        }
-
-        if self.invert {
-            out.write_all(b"\x1B[39;49m")?;
-        } else {
-            out.write_all(b"\x1B[39m")?;
+            escaping = self.colorize_grapheme(out, grapheme, escaping)?;
         }
+        self.write_reset(out)?;
         out.flush()
     }
 
@@ -344,19 +608,18 @@ impl Lolcrab {
         input: &mut impl BufRead,
         out: &mut impl Write,
     ) -> std::io::Result<()> {
-        // hide the cursor
-        out.write_all(b"\x1B[?25l")?;
+        // shows the cursor again, also on errors and panics
+        let mut out = TerminalGuard::new(out);
+        out.hide_cursor()?;
 
         input.for_byte_line(|line| {
-            self.colorize_anim(line, out)?;
+            self.colorize_anim(line, &mut *out)?;
             Ok(true)
-        })?;
-
-        // show the cursor
-        out.write_all(b"\x1B[?25h")?;
-        Ok(())
+        })
     }
 
+    /// Colorize the input as it arrives, without waiting for whole lines.
+    ///
     /// # Errors
     ///
     /// Will return `Err` if `input` or `out` cause I/O errors
@@ -365,105 +628,59 @@ impl Lolcrab {
         input: &mut impl BufRead,
         out: &mut impl Write,
     ) -> std::io::Result<()> {
-        input.for_byte_line_with_terminator(|line| {
-            self.colorize(line, out)?;
-            Ok(true)
-        })
-    }
-}
-
-#[cfg(feature = "cli")]
-impl From<Opt> for Lolcrab {
-    fn from(cmd: Opt) -> Self {
-        if let Some(seed) = cmd.seed {
-            fastrand::seed(seed);
-        }
-
-        let grad: Box<dyn colorgrad::Gradient> = if let Some(ref css_grad) = cmd.custom {
-            Box::new(
-                colorgrad::GradientBuilder::new()
-                    .css(css_grad)
-                    .mode(colorgrad::BlendMode::Oklab)
-                    .build::<colorgrad::CatmullRomGradient>()
-                    .unwrap_or_else(|e| {
-                        println!("Error: {e}");
-                        process::exit(1);
-                    }),
-            )
-        } else if cmd.random_colors.is_some() {
-            let n = cmd.random_colors.unwrap();
-            let colors = (0..n).map(|_| random_color()).collect::<Vec<_>>();
-            Box::new(
-                colorgrad::GradientBuilder::new()
-                    .colors(&colors)
-                    .mode(colorgrad::BlendMode::Oklab)
-                    .build::<colorgrad::CatmullRomGradient>()
-                    .unwrap(),
-            )
-        } else if cmd.lolcat {
-            Gradient::Lolcat.to_gradient()
-        } else {
-            cmd.gradient.to_gradient()
-        };
-
-        let grad = if let Some(n) = cmd.sharp {
-            if n > 1 {
-                Box::new(grad.sharp(n as u16, 0.15))
-            } else {
-                grad
-            }
-        } else {
-            grad
-        };
-
-        let mut lol = Self::new(Some(grad), None);
-        lol.set_noise_scale(cmd.scale);
-        lol.set_invert(cmd.invert);
-        if let Some(speed) = cmd.speed {
-            lol.set_anim_speed(speed);
-        }
-        if let Some(duration) = cmd.duration {
-            lol.set_anim_duration(duration as usize);
-        }
-        if let Some(angle) = cmd.angle {
-            lol.set_linear(true);
-            lol.set_angle(angle);
-        }
-        if let Some(spread) = cmd.spread {
-            lol.set_linear(true);
-            lol.set_spread(spread);
-        }
-        if let Some(offset) = cmd.offset {
-            lol.set_linear(true);
-            lol.set_offset(offset);
-        }
-        if cmd.linear || cmd.lolcat {
-            lol.set_linear(true);
+        let mut stream = Stream::default();
+        loop {
+            let buf = match input.fill_buf() {
+                Ok([]) => break,
+                Ok(buf) => buf,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            stream.feed(self, buf, out)?;
+            let n = buf.len();
+            input.consume(n);
+            out.flush()?;
         }
-        lol
+        stream.finish(self, out)?;
+        out.flush()
     }
 }
 
-#[cfg(feature = "cli")]
-fn random_color() -> Color {
-    if fastrand::bool() {
-        Color::from_hwba(fastrand::f32() * 360.0, fastrand::f32() * 0.5, 0.0, 1.0)
-    } else {
-        Color::from_hwba(fastrand::f32() * 360.0, 0.0, fastrand::f32() * 0.3, 1.0)
+impl fmt::Debug for Lolcrab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let colors: Vec<_> = self
+            .gradient
+            .colors(5)
+            .iter()
+            .map(Color::to_css_hex)
+            .collect();
+        f.debug_struct("Lolcrab")
+            .field("gradient", &colors)
+            .field("noise_scale", &self.noise_scale)
+            .field("invert", &self.invert)
+            .field("tab_width", &self.tab_width)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("linear", &self.linear)
+            .field("angle", &self.angle)
+            .field("spread", &self.spread)
+            .field("offset", &self.offset)
+            .field("anim_duration", &self.anim_duration)
+            .field("anim_sleep", &self.anim_sleep)
+            .field("lut_size", &self.lut_size)
+            .field("compress", &self.compress)
+            .field("tolerance", &self.tolerance)
+            .finish_non_exhaustive()
     }
 }
 
-// Reference http://www.w3.org/TR/2008/REC-WCAG20-20081211/#relativeluminancedef
-fn color_luminance(col: &Color) -> f32 {
-    fn lum(t: f32) -> f32 {
-        if t <= 0.03928 {
-            t / 12.92
-        } else {
-            ((t + 0.055) / 1.055).powf(2.4)
-        }
-    }
-
-    0.2126 * lum(col.r) + 0.7152 * lum(col.g) + 0.0722 * lum(col.b)
+// Euclidean distance
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f32>()
+        .sqrt()
 }
 
 // Map t from range [a, b] to range [c, d]
@@ -478,9 +695,10 @@ fn modulo(x: f32, y: f32) -> f32 {
 
 #[cfg(test)]
 mod tests {
-    use super::Lolcrab;
+    use super::{Continuity, Lolcrab};
+    use std::sync::Arc;
 
-    fn new_lol(seed: u32) -> Lolcrab {
+    pub(crate) fn new_lol(seed: u32) -> Lolcrab {
         Lolcrab::new(None, Some(Box::new(noise::OpenSimplex::new(seed))))
     }
 
@@ -490,15 +708,125 @@ mod tests {
 
         let mut lol1 = new_lol(7);
         let mut out1 = Vec::new();
-        lol1.colorize(&text.as_bytes(), &mut out1).unwrap();
+        lol1.colorize(text.as_bytes(), &mut out1).unwrap();
 
         let mut lol2 = new_lol(7);
         let mut out2 = Vec::new();
-        lol2.colorize_str(&text, &mut out2).unwrap();
+        lol2.colorize_str(text, &mut out2).unwrap();
 
         assert_eq!(out1, out2);
     }
 
+    #[test]
+    fn with_seed() {
+        let text = "Lolcrab\nis the best";
+        let colorize = |lol: &mut Lolcrab| {
+            let mut out = Vec::new();
+            lol.colorize_str(text, &mut out).unwrap();
+            lol.randomize_position();
+            lol.colorize_str(text, &mut out).unwrap();
+            out
+        };
+
+        let mut lol1 = Lolcrab::with_seed(7);
+        fastrand::seed(1);
+        let out1 = colorize(&mut lol1);
+
+        fastrand::seed(2);
+        let _ = fastrand::u64(..);
+        let mut lol2 = Lolcrab::with_seed(7);
+        assert_eq!(out1, colorize(&mut lol2));
+
+        lol1.set_linear(true);
+        lol2.set_linear(true);
+        assert_eq!(colorize(&mut lol1), colorize(&mut lol2));
+
+        assert_ne!(out1, colorize(&mut Lolcrab::with_seed(8)));
+    }
+
+    #[test]
+    fn paragraph_continuity() {
+        let colorize = |lol: &mut Lolcrab, text: &str| {
+            let mut out = Vec::new();
+            lol.colorize_str(text, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let paragraph = "Lolcrab\nis the best\n";
+
+        let mut lol = Lolcrab::with_seed(5);
+        lol.set_paragraph_continuity(Continuity::Reset);
+        let out = colorize(&mut lol, &format!("{paragraph}\n \n{paragraph}"));
+        let first = colorize(&mut Lolcrab::with_seed(5), paragraph);
+        let colors = first.strip_suffix("\x1B[39m").unwrap();
+        assert!(out.starts_with(colors));
+        assert!(out.ends_with(&first));
+
+        // not in the middle of a paragraph
+        let mut lol = Lolcrab::with_seed(5);
+        lol.set_paragraph_continuity(Continuity::Reset);
+        let out = colorize(&mut lol, &paragraph.repeat(2));
+        assert_eq!(
+            out,
+            colorize(&mut Lolcrab::with_seed(5), &paragraph.repeat(2))
+        );
+
+        let mut lol = Lolcrab::with_seed(5);
+        lol.colorize_str(paragraph, &mut Vec::new()).unwrap();
+        lol.apply_continuity(Continuity::Reset);
+        assert_eq!(colorize(&mut lol, paragraph), first);
+        lol.apply_continuity(Continuity::Randomize);
+        assert_ne!(colorize(&mut lol, paragraph), first);
+    }
+
+    #[test]
+    fn fit() {
+        let mut lol = Lolcrab::with_seed(1);
+        let corners = |lol: &mut Lolcrab, width: isize, height: isize| {
+            let mut positions = Vec::new();
+            for (x, y) in [
+                (0, 0),
+                (width - 1, 0),
+                (0, height - 1),
+                (width - 1, height - 1),
+            ] {
+                lol.x = x;
+                lol.y = y;
+                positions.push((lol.position() * 1000.0).round() / 1000.0);
+            }
+            positions
+        };
+
+        lol.set_angle(0.0);
+        lol.fit_text("Lolcrab\tis\nthe best\n".as_bytes());
+        assert_eq!(corners(&mut lol, 10, 2), [0.0, 1.0, 0.0, 1.0]);
+
+        lol.set_angle(270.0);
+        lol.fit(5, 3);
+        assert_eq!(corners(&mut lol, 5, 3), [1.0, 1.0, 0.0, 0.0]);
+
+        lol.set_angle(45.0);
+        lol.fit(5, 3);
+        let positions = corners(&mut lol, 5, 3);
+        assert_eq!((positions[0], positions[3]), (0.0, 1.0));
+
+        lol.fit(1, 1);
+        assert_eq!(corners(&mut lol, 1, 1), [0.0; 4]);
+    }
+
+    #[test]
+    fn stretch() {
+        let mut lol = Lolcrab::with_seed(1);
+        lol.set_angle(30.0);
+        lol.stretch(8);
+        let mut positions = Vec::new();
+        for (x, y) in [(0, 0), (4, 0), (0, 5), (4, 5), (8, 5)] {
+            lol.x = x;
+            lol.y = y;
+            positions.push(lol.position());
+        }
+        assert_eq!(positions, [0.0, 0.5, 0.0, 0.5, 0.0]);
+    }
+
     #[test]
     fn str_width() {
         let mut lol = Lolcrab::new(None, None);
@@ -561,7 +889,7 @@ mod tests {
         let text = "foobar\n";
 
         let mut lol1 = new_lol(0);
-        lol1.colorize(&text.as_bytes(), &mut Vec::new()).unwrap();
+        lol1.colorize(text.as_bytes(), &mut Vec::new()).unwrap();
 
         let mut lol2 = new_lol(0);
         lol2.step_row(1);
@@ -580,6 +908,147 @@ mod tests {
         assert_eq!(lol1.get_color().to_rgba8(), lol2.get_color().to_rgba8());
     }
 
+    #[test]
+    fn lut() {
+        let mut lol1 = new_lol(5);
+        let mut lol2 = new_lol(5);
+        lol2.set_lut_size(0);
+
+        for i in 0..200 {
+            if i == 100 {
+                // the table is rebuilt for the new gradient
+                lol1.gradient = Arc::new(colorgrad::preset::turbo());
+                lol2.gradient = Arc::new(colorgrad::preset::turbo());
+            }
+            lol1.step_col(1);
+            lol2.step_col(1);
+            let (c1, fg1) = lol1.get_colors();
+            let (c2, fg2) = lol2.get_colors();
+            for (a, b) in c1.iter().chain(&fg1).zip(c2.iter().chain(&fg2)) {
+                assert!(a.abs_diff(*b) <= 2);
+            }
+        }
+    }
+
+    // Foreground color of every visible character, as a terminal would show it
+    pub(crate) fn render(out: &[u8]) -> Vec<(char, Option<Vec<u8>>)> {
+        let mut chars = Vec::new();
+        let mut fg = None;
+        let mut rest = std::str::from_utf8(out).unwrap();
+        while let Some(c) = rest.chars().next() {
+            if c == '\x1B' {
+                let end = rest.find('m').unwrap();
+                let params: Vec<u8> = rest[2..end]
+                    .split(';')
+                    .map(|n| n.parse().unwrap())
+                    .collect();
+                fg = match params[..] {
+                    [38, 2, ..] => Some(params[2..].to_vec()),
+                    _ => None,
+                };
+                rest = &rest[end + 1..];
+                continue;
+            }
+            if !c.is_whitespace() {
+                chars.push((c, fg.clone()));
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        chars
+    }
+
+    #[test]
+    fn compress() {
+        let text = "Lolcrab is  the\tbest\n🦀 Rust \n".repeat(20);
+
+        let mut lol1 = new_lol(2);
+        lol1.set_noise_scale(0.001);
+        let mut out1 = Vec::new();
+        lol1.colorize_str(&text, &mut out1).unwrap();
+
+        let mut lol2 = new_lol(2);
+        lol2.set_noise_scale(0.001);
+        lol2.set_compress(true);
+        let mut out2 = Vec::new();
+        lol2.colorize_str(&text, &mut out2).unwrap();
+
+        assert!(out2.len() * 2 < out1.len());
+        assert_eq!(render(&out1), render(&out2));
+
+        let mut lol3 = new_lol(2);
+        lol3.set_noise_scale(0.001);
+        lol3.set_compress(true);
+        lol3.set_color_tolerance(0.05);
+        let mut out3 = Vec::new();
+        lol3.colorize_str(&text, &mut out3).unwrap();
+
+        assert!(out3.len() < out2.len());
+    }
+
+    #[test]
+    fn eq_read_lines() {
+        let text = "Lolcrab\tis\r\nthe best\n\n\x1B[1m🦀\x1B[0m\nno newline";
+
+        let mut lol1 = new_lol(4);
+        let mut out1 = Vec::new();
+        for line in text.split_inclusive('\n') {
+            lol1.colorize_str(line, &mut out1).unwrap();
+        }
+
+        let mut lol2 = new_lol(4);
+        let mut out2 = Vec::new();
+        lol2.colorize_read(&mut text.as_bytes(), &mut out2).unwrap();
+
+        assert_eq!(out1, out2);
+    }
+
+    // Endless input without line breaks, fails after `n` chunks
+    struct Endless(usize);
+
+    impl std::io::Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0 == 0 {
+                return Err(std::io::Error::other("end"));
+            }
+            self.0 -= 1;
+            buf.fill(b'y');
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn long_line() {
+        let mut lol = new_lol(0);
+        let mut out = Vec::new();
+        let mut input = std::io::BufReader::with_capacity(64, Endless(3));
+
+        assert!(lol.colorize_read(&mut input, &mut out).is_err());
+        assert_eq!(out.iter().filter(|&&b| b == b'y').count(), 3 * 64 - 1);
+    }
+
+    #[test]
+    fn auto_traits() {
+        fn assert_traits<T: Send + Sync + Clone + std::fmt::Debug>() {}
+        assert_traits::<Lolcrab>();
+    }
+
+    #[test]
+    fn clone_in_thread() {
+        let text = "Lolcrab\nRust";
+        let mut lol = new_lol(42);
+        let mut lol2 = lol.clone();
+
+        let handle = std::thread::spawn(move || {
+            let mut out = Vec::new();
+            lol2.colorize_str(text, &mut out).unwrap();
+            out
+        });
+
+        let mut out = Vec::new();
+        lol.colorize_str(text, &mut out).unwrap();
+        assert_eq!(out, handle.join().unwrap());
+    }
+
     #[test]
     fn noise_position() {
         let mut lol = Lolcrab::new(None, None);
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use colorgrad::{Color, Gradient};
+
+pub(crate) type Rgb = [u8; 3];
+
+/// Gradient colors sampled at evenly spaced positions across its domain
+pub(crate) struct Lut {
+    gradient: Arc<dyn Gradient + Send + Sync>,
+    dmin: f32,
+    dmax: f32,
+    // gradient color, and the text color used on top of it in invert mode
+    colors: Vec<(Rgb, Rgb)>,
+}
+
+impl Lut {
+    pub(crate) fn new(gradient: &Arc<dyn Gradient + Send + Sync>, size: usize) -> Self {
+        let (dmin, dmax) = gradient.domain();
+        Self {
+            gradient: Arc::clone(gradient),
+            dmin,
+            dmax,
+            colors: gradient.colors(size).iter().map(colors).collect(),
+        }
+    }
+
+    /// Was this table built from `gradient` with `size` entries
+    pub(crate) fn is_for(&self, gradient: &Arc<dyn Gradient + Send + Sync>, size: usize) -> bool {
+        Arc::ptr_eq(&self.gradient, gradient) && self.colors.len() == size
+    }
+
+    /// Returns `None` if `t` is outside the gradient's domain
+    #[inline]
+    pub(crate) fn get(&self, t: f32) -> Option<(Rgb, Rgb)> {
+        if !(self.dmin..=self.dmax).contains(&t) {
+            return None;
+        }
+        let n = (self.colors.len() - 1) as f32;
+        let i = ((t - self.dmin) / (self.dmax - self.dmin) * n).round() as usize;
+        self.colors.get(i).copied()
+    }
+}
+
+/// Gradient color as RGB, and the text color used on top of it in invert mode
+pub(crate) fn colors(col: &Color) -> (Rgb, Rgb) {
+    let [r, g, b, _] = col.to_rgba8();
+
+    let lum = color_luminance(col);
+    let eps = 0.013;
+
+    let v = if lum < eps {
+        crate::remap(lum, eps, 0.0, 0.22, 0.2)
+    } else {
+        crate::remap(lum, eps, 1.0, 0.0, 0.7)
+    };
+    let [x, y, z, _] = Color::new(v, v, v, 1.0).to_rgba8();
+
+    ([r, g, b], [x, y, z])
+}
+
+// Reference http://www.w3.org/TR/2008/REC-WCAG20-20081211/#relativeluminancedef
+fn color_luminance(col: &Color) -> f32 {
+    fn lum(t: f32) -> f32 {
+        if t <= 0.03928 {
+            t / 12.92
+        } else {
+            ((t + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    0.2126 * lum(col.r) + 0.7152 * lum(col.g) + 0.0722 * lum(col.b)
+}
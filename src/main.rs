@@ -1,50 +1,101 @@
 #![warn(clippy::pedantic, clippy::nursery)]
 
+use std::collections::BTreeMap;
 use std::ffi::OsString;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, IsTerminal, Write};
 use std::path::PathBuf;
+use std::process::ExitCode;
 
-use clap::{CommandFactory, Parser, ValueEnum};
-use lolcrab::{Gradient, Lolcrab, Opt};
+use clap::{CommandFactory, Parser};
+use lolcrab::{Columns, Config, CustomGradient, Font, Gradient, Lolcrab, Opt};
+
+mod config_file;
+mod gradient_file;
 
 #[cfg(feature = "mimalloc")]
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-fn config_file() -> Option<PathBuf> {
-    std::env::var("LOLCRAB_CONFIG_PATH")
-        .ok()
-        .map(PathBuf::from)
-        .filter(|config_path| config_path.is_file())
-        .or_else(|| Some(dirs::config_dir()?.join("lolcrab").join("config")))
+fn colorize_input(
+    lol: &mut Lolcrab,
+    opt: &Opt,
+    font: Option<&Font>,
+    columns: Option<&Columns>,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<(), io::Error> {
+    let frame = opt.frame();
+    if font.is_none() && frame.is_none() && !opt.fit {
+        return colorize_text(lol, opt, columns, input, out);
+    }
+    // the banner, the box and the size of the text need the whole input
+    let mut text = Vec::new();
+    input.read_to_end(&mut text)?;
+    if let Some(font) = font {
+        text = font.render(&String::from_utf8_lossy(&text)).into_bytes();
+    }
+    if let Some(frame) = frame {
+        text = lol
+            .frame(&String::from_utf8_lossy(&text), &frame)
+            .into_bytes();
+    }
+    if opt.fit {
+        lol.fit_text(&text);
+    }
+    colorize_text(lol, opt, columns, &mut text.as_slice(), out)
 }
 
-fn read_config_file() -> Vec<OsString> {
-    let mut args = Vec::new();
-    let Some(path) = config_file() else {
-        return args;
-    };
-    if !path.exists() {
-        return args;
+fn colorize_text(
+    lol: &mut Lolcrab,
+    opt: &Opt,
+    columns: Option<&Columns>,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<(), io::Error> {
+    if let Some(columns) = columns {
+        return lol.colorize_columns(input, out, columns);
+    }
+    if opt.animate {
+        return lol.colorize_read_anim(input, out);
     }
-    let file = File::open(path).unwrap();
-    let reader = BufReader::new(file);
+    #[cfg(feature = "rayon")]
+    if opt.parallel {
+        return lol.colorize_read_par(input, out);
+    }
+    lol.colorize_read(input, out)
+}
 
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') {
+// Show the preset gradients and the gradients from the config file
+fn list_presets(
+    lol: &mut Lolcrab,
+    gradients: &BTreeMap<String, CustomGradient>,
+    is_terminal: bool,
+    out: &mut impl Write,
+) -> Result<(), io::Error> {
+    // gradients from the config file replace presets with the same name
+    let presets = Gradient::ALL
+        .iter()
+        .filter(|g| !gradients.contains_key(g.name()))
+        .map(|g| (g.name(), Ok(g.to_gradient())));
+    let custom = gradients.iter().map(|(name, g)| (name.as_str(), g.build()));
+
+    for (name, gradient) in presets.chain(custom) {
+        if !is_terminal {
+            writeln!(out, "{name}")?;
             continue;
         }
-        if let Some(line_args) = shlex::split(line) {
-            args.extend(line_args.into_iter().map(OsString::from));
-        } else {
-            eprintln!("Failed to parse config line '{line}'");
+        match gradient {
+            Ok(gradient) => {
+                writeln!(out, "\n{name}\n")?;
+                lol.gradient = gradient.into();
+                lol.randomize_position();
+                lol.colorize_str(SAMPLE_TEXT, out)?;
+            }
+            Err(e) => eprintln!("lolcrab: gradient '{name}': {e}"),
         }
     }
-
-    args
+    Ok(())
 }
 
 const SAMPLE_TEXT: &str = "\
@@ -57,92 +108,267 @@ oooo............oooo...github.com/mazznoer/lolcrab...o8.
 o888o.`Y8bod8P'.o888o.`Y8bod8P'.d888b....`Y888''8o..`Y8bod8P.
 ";
 
-fn main() -> Result<(), io::Error> {
-    let mut args_cfg = read_config_file();
-    let mut args_cli = std::env::args_os();
-    args_cfg.insert(0, args_cli.next().unwrap());
-    args_cfg.extend(args_cli);
+// Width of the terminal for `--stretch`
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+        .or_else(|| {
+            terminal_size::terminal_size_of(io::stdout()).map(|(width, _)| usize::from(width.0))
+        })
+        .unwrap_or(80)
+}
 
-    let opt = Opt::parse_from(args_cfg);
-    let mut stdout = io::stdout().lock();
-    let is_terminal = stdout.is_terminal();
-    let mut stdout = io::BufWriter::new(&mut stdout);
+// Resets the terminal and exits with 128 plus the signal number on SIGINT,
+// SIGTERM and SIGHUP. The signals are blocked before any other thread starts,
+// so only the handler thread takes them. Stdout stays locked until the exit,
+// so no color written by the main thread can follow the reset.
+//
+// SIGPIPE is ignored by the Rust runtime, writing to a closed pipe fails with
+// `BrokenPipe` instead, handled in `main`. A terminal doesn't close like a pipe.
+#[cfg(unix)]
+fn restore_on_signal() {
+    use nix::sys::signal::{SigSet, Signal};
+
+    let mut signals = SigSet::empty();
+    for signal in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGHUP] {
+        signals.add(signal);
+    }
+    if signals.thread_block().is_err() {
+        return;
+    }
+    std::thread::spawn(move || {
+        if let Ok(signal) = signals.wait() {
+            let mut stdout = io::stdout().lock();
+            let _ = lolcrab::restore_terminal(&mut stdout);
+            std::process::exit(128 + signal as i32);
+        }
+    });
+}
+
+// Ctrl-C, like SIGINT on Unix
+#[cfg(not(unix))]
+fn restore_on_signal() {
+    let _ = ctrlc::set_handler(|| {
+        let mut stdout = io::stdout().lock();
+        let _ = lolcrab::restore_terminal(&mut stdout);
+        std::process::exit(130);
+    });
+}
+
+// A bundled font or a font file
+fn load_font(name: &str) -> Result<Font, String> {
+    if let Some(font) = Font::builtin(name) {
+        return Ok(font);
+    }
+    let s = std::fs::read_to_string(name).map_err(|e| format!("{name}: {}", error_reason(&e)))?;
+    s.parse().map_err(|e| format!("{name}: {e}"))
+}
+
+// The error message without the " (os error N)" suffix
+fn error_reason(e: &io::Error) -> String {
+    let s = e.to_string();
+    match s.find(" (os error ") {
+        Some(i) => s[..i].to_string(),
+        None => s,
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        // the reader of the output is gone, like `head`
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("lolcrab: {}", error_reason(&e));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Command line options on top of the config file
+fn parse_options() -> Result<(Opt, Config), config_file::Error> {
+    let args_cli: Vec<OsString> = std::env::args_os().collect();
+    // the profile is needed before the config file can be read
+    let profile = Opt::command()
+        .ignore_errors(true)
+        .get_matches_from(&args_cli)
+        .get_one::<String>("profile")
+        .cloned();
 
-    let mut lol: Lolcrab = opt.clone().into();
+    let config_file = config_file::read(profile.as_deref())?;
+    let mut args = config_file.args;
+    let mut args_cli = args_cli.into_iter();
+    args.insert(0, args_cli.next().unwrap_or_else(|| "lolcrab".into()));
+    args.extend(args_cli);
+    let opt = Opt::parse_from(args);
 
+    let mut config = Config::from(&opt);
+    config.gradients = config_file.gradients;
+    if let Some(path) = &opt.gradient_file {
+        config.gradient = gradient_file::read(path)?;
+    }
+    if opt.print_colors || opt.dump_config {
+        // the printed options have to be the ones used
+        config.seed.get_or_insert_with(|| fastrand::u64(..));
+    }
+    Ok((opt, config))
+}
+
+// Print help, version, config file location or presets instead of colorizing input
+fn print_info(
+    lol: &mut Lolcrab,
+    opt: &Opt,
+    config: &Config,
+    is_terminal: bool,
+    stdout: &mut impl Write,
+) -> Result<bool, io::Error> {
     if opt.help {
         if opt.animate {
             lol.colorize_read_anim(
                 &mut BufReader::new(Opt::command().render_help().to_string().as_bytes()),
-                &mut stdout,
+                stdout,
             )?;
         } else {
-            lol.colorize_str(
-                &Opt::command().render_help().ansi().to_string(),
-                &mut stdout,
-            )?;
+            lol.colorize_str(&Opt::command().render_help().ansi().to_string(), stdout)?;
         }
         stdout.flush()?;
-        return Ok(());
+        return Ok(true);
     }
 
     if opt.version {
-        lol.colorize_str(&Opt::command().render_long_version(), &mut stdout)?;
+        lol.colorize_str(&Opt::command().render_long_version(), stdout)?;
         stdout.flush()?;
-        return Ok(());
+        return Ok(true);
     }
 
     if opt.config_file {
-        let Some(cfg_path) = config_file() else {
-            return Ok(());
+        let Some(cfg_path) = config_file::path() else {
+            return Ok(true);
         };
         let cfg_path = format!("{}\n", cfg_path.display());
         if is_terminal {
-            lol.colorize_str(&cfg_path, &mut stdout)?;
+            lol.colorize_str(&cfg_path, stdout)?;
         } else {
             write!(stdout, "{cfg_path}")?;
         }
         stdout.flush()?;
-        return Ok(());
+        return Ok(true);
     }
 
     if opt.presets {
-        for g in Gradient::value_variants() {
-            let name = format!("{g:?}").to_lowercase();
-            let name = if name == "rdylgn" { "rd-yl-gn" } else { &name };
-            if is_terminal {
-                writeln!(stdout, "\n{name}\n")?;
-                lol.gradient = g.to_gradient();
-                lol.randomize_position();
-                lol.colorize_str(SAMPLE_TEXT, &mut stdout)?;
-            } else {
-                writeln!(stdout, "{name}")?;
-            }
-        }
+        list_presets(lol, &config.gradients, is_terminal, stdout)?;
         stdout.flush()?;
-        return Ok(());
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+fn run() -> Result<ExitCode, io::Error> {
+    let (mut opt, config) = match parse_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("lolcrab: {e}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    // not locked, so the interrupt handler can write to it
+    let stdout = io::stdout();
+    let is_terminal = stdout.is_terminal();
+    let mut stdout = io::BufWriter::new(stdout);
+
+    if is_terminal {
+        // don't leave the terminal colored or without a cursor
+        restore_on_signal();
     }
 
-    for path in opt.files {
+    let mut lol = match config.build() {
+        Ok(settings) => settings.lolcrab(),
+        Err(e) => {
+            eprintln!("lolcrab: {e}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let font = match opt
+        .banner
+        .then(|| load_font(opt.font.as_deref().unwrap_or("standard")))
+    {
+        Some(Ok(font)) => Some(font),
+        Some(Err(e)) => {
+            eprintln!("lolcrab: {e}");
+            return Ok(ExitCode::FAILURE);
+        }
+        None => None,
+    };
+    let columns = match opt.columns() {
+        Ok(columns) => columns,
+        Err(e) => {
+            eprintln!("lolcrab: invalid separator: {e}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    if let Some(width) = opt.stretch {
+        let width = if width == 0 { terminal_width() } else { width };
+        // the same width for `--dump-config`
+        opt.stretch = Some(width);
+        lol.stretch(width);
+        if let Some(offset) = opt.offset {
+            lol.set_offset(offset);
+        }
+    }
+    if let Some(css) = config.random_css().filter(|_| opt.print_colors) {
+        eprintln!("--custom '{css}'");
+    }
+    if opt.dump_config {
+        eprint!("{}", config_file::dump(&config, &lol, &opt));
+    }
+
+    if print_info(&mut lol, &opt, &config, is_terminal, &mut stdout)? {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // like `cat`, a file that can't be read doesn't stop the others
+    let mut code = ExitCode::SUCCESS;
+    for (i, path) in opt.files.iter().enumerate() {
+        if i > 0 {
+            lol.apply_continuity(opt.file_continuity());
+        }
         #[allow(clippy::cmp_owned)]
-        if path == PathBuf::from("-") {
+        let result = if *path == PathBuf::from("-") {
             let mut stdin = io::stdin().lock();
-            if opt.animate {
-                lol.colorize_read_anim(&mut stdin, &mut stdout)?;
-            } else {
-                lol.colorize_read(&mut stdin, &mut stdout)?;
-            }
+            colorize_input(
+                &mut lol,
+                &opt,
+                font.as_ref(),
+                columns.as_ref(),
+                &mut stdin,
+                &mut stdout,
+            )
         } else {
-            let f = File::open(path).unwrap();
-            let mut b = BufReader::new(f);
-            if opt.animate {
-                lol.colorize_read_anim(&mut b, &mut stdout)?;
-            } else {
-                lol.colorize_read(&mut b, &mut stdout)?;
+            File::open(path).and_then(|f| {
+                let mut b = BufReader::new(f);
+                colorize_input(
+                    &mut lol,
+                    &opt,
+                    font.as_ref(),
+                    columns.as_ref(),
+                    &mut b,
+                    &mut stdout,
+                )
+            })
+        };
+        if let Err(e) = result {
+            if e.kind() == io::ErrorKind::BrokenPipe {
+                return Err(e);
             }
+            eprintln!("lolcrab: {}: {}", path.display(), error_reason(&e));
+            code = ExitCode::FAILURE;
         }
     }
 
     stdout.flush()?;
-    Ok(())
+    Ok(code)
 }
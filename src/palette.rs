@@ -0,0 +1,158 @@
+use std::f32::consts::{PI, TAU};
+
+use colorgrad::Color;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the colors of a random gradient are chosen.
+///
+/// Except for `Any`, the colors are created in Oklch space, so colors of the
+/// same lightness look equally bright.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum RandomScheme {
+    /// Independent random colors
+    #[default]
+    Any,
+    /// Neighboring hues
+    Analogous,
+    /// A hue and its opposite
+    Complementary,
+    /// Three evenly spaced hues
+    Triadic,
+    /// Four evenly spaced hues
+    Tetradic,
+    /// Light colors with little chroma
+    Pastel,
+    /// Bright, saturated colors around the color wheel
+    Neon,
+    /// A single hue in different lightness
+    Monochrome,
+}
+
+impl RandomScheme {
+    pub(crate) fn colors(self, n: u8, rng: &mut fastrand::Rng) -> Vec<Color> {
+        let n = usize::from(n);
+        let hue = rng.f32() * TAU;
+
+        // hues of the color groups, each group gets a part of the gradient
+        let hues = |count: usize, step: f32| -> Vec<f32> {
+            (0..count).map(|i| hue + i as f32 * step).collect()
+        };
+        let group_hue = |hues: &[f32], i: usize| hues[i * hues.len() / n.max(1)];
+
+        (0..n)
+            .map(|i| {
+                let jitter = (rng.f32() - 0.5) * 30f32.to_radians();
+                let (l, c, h) = match self {
+                    Self::Any => return any_color(rng),
+                    Self::Analogous => {
+                        // at most 30° to both sides, however many colors
+                        let step = 60f32.to_radians() / (n - 1).max(1) as f32;
+                        let h = hue + (i as f32 - (n - 1) as f32 / 2.0) * step;
+                        (range(rng, 0.55, 0.8), range(rng, 0.1, 0.16), h)
+                    }
+                    Self::Complementary => (
+                        range(rng, 0.55, 0.8),
+                        range(rng, 0.1, 0.16),
+                        group_hue(&hues(2, PI), i) + jitter,
+                    ),
+                    Self::Triadic => (
+                        range(rng, 0.55, 0.8),
+                        range(rng, 0.1, 0.16),
+                        group_hue(&hues(3, TAU / 3.0), i) + jitter,
+                    ),
+                    Self::Tetradic => (
+                        range(rng, 0.55, 0.8),
+                        range(rng, 0.1, 0.16),
+                        group_hue(&hues(4, TAU / 4.0), i) + jitter,
+                    ),
+                    Self::Pastel => (
+                        range(rng, 0.86, 0.93),
+                        range(rng, 0.04, 0.08),
+                        rng.f32() * TAU,
+                    ),
+                    Self::Neon => (
+                        range(rng, 0.72, 0.82),
+                        range(rng, 0.22, 0.3),
+                        hue + i as f32 * TAU / n as f32,
+                    ),
+                    Self::Monochrome => {
+                        let t = if n > 1 {
+                            i as f32 / (n - 1) as f32
+                        } else {
+                            0.5
+                        };
+                        (0.35 + t * 0.55, range(rng, 0.06, 0.12), hue)
+                    }
+                };
+                Color::from_oklcha(l, c, h, 1.0).clamp()
+            })
+            .collect()
+    }
+}
+
+fn range(rng: &mut fastrand::Rng, min: f32, max: f32) -> f32 {
+    min + rng.f32() * (max - min)
+}
+
+fn any_color(rng: &mut fastrand::Rng) -> Color {
+    if rng.bool() {
+        Color::from_hwba(rng.f32() * 360.0, rng.f32() * 0.5, 0.0, 1.0)
+    } else {
+        Color::from_hwba(rng.f32() * 360.0, 0.0, rng.f32() * 0.3, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomScheme;
+
+    #[test]
+    fn colors() {
+        let schemes = [
+            RandomScheme::Any,
+            RandomScheme::Analogous,
+            RandomScheme::Complementary,
+            RandomScheme::Triadic,
+            RandomScheme::Tetradic,
+            RandomScheme::Pastel,
+            RandomScheme::Neon,
+            RandomScheme::Monochrome,
+        ];
+        for scheme in schemes {
+            for n in [1, 2, 7, 15] {
+                let colors = scheme.colors(n, &mut fastrand::Rng::with_seed(1));
+                assert_eq!(colors.len(), usize::from(n));
+                assert!(colors
+                    .iter()
+                    .all(|c| c.to_array().iter().all(|v| (0.0..=1.0).contains(v))));
+                assert_eq!(colors, scheme.colors(n, &mut fastrand::Rng::with_seed(1)));
+            }
+        }
+
+        // lighter and lighter
+        let colors = RandomScheme::Monochrome.colors(5, &mut fastrand::Rng::new());
+        let lightness: Vec<_> = colors.iter().map(|c| c.to_oklaba()[0]).collect();
+        assert!(lightness.windows(2).all(|w| w[0] < w[1]));
+
+        // neighboring hues, even with the most colors
+        for seed in 0..20 {
+            let colors = RandomScheme::Analogous.colors(15, &mut fastrand::Rng::with_seed(seed));
+            let hue = |c: &colorgrad::Color| {
+                let [_, a, b, _] = c.to_oklaba();
+                b.atan2(a).to_degrees()
+            };
+            let first = hue(&colors[0]);
+            let spread: Vec<_> = colors
+                .iter()
+                .map(|c| (hue(c) - first + 540.0) % 360.0 - 180.0)
+                .collect();
+            let min = spread.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = spread.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            // gamut clamping may move the hues a little
+            assert!(max - min < 70.0);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::sync::mpsc;
+use std::thread;
+
+use bstr::ByteSlice;
+use rayon::prelude::*;
+
+use crate::{Continuity, Lolcrab};
+
+// Input is read in batches of about this size, each split into pieces
+// which are colorized concurrently
+const BATCH_SIZE: usize = 4 * 1024 * 1024;
+const PIECE_SIZE: usize = 64 * 1024;
+
+impl Lolcrab {
+    /// Parallel version of [`Lolcrab::colorize_read`].
+    ///
+    /// The input is split into chunks of lines which are colorized on the
+    /// `rayon` thread pool. The output is identical to [`Lolcrab::colorize_read`].
+    /// Unlike [`Lolcrab::colorize_read`], whole lines are kept in memory.
+    /// With a paragraph continuity other than [`Continuity::Continue`] the
+    /// input is colorized sequentially.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `input` or `out` cause I/O errors
+    pub fn colorize_read_par(
+        &mut self,
+        input: &mut impl BufRead,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        // the row of a piece depends on the paragraphs before it
+        if self.paragraph != Continuity::Continue {
+            return self.colorize_read(input, out);
+        }
+        self.colorize_read_par_with(input, out, BATCH_SIZE, PIECE_SIZE)
+    }
+
+    fn colorize_read_par_with(
+        &mut self,
+        input: &mut impl BufRead,
+        out: &mut impl Write,
+        batch_size: usize,
+        piece_size: usize,
+    ) -> std::io::Result<()> {
+        let mut batch = Vec::new();
+        let mut eof = false;
+        while !eof {
+            batch.clear();
+            while batch.len() < batch_size {
+                if input.read_until(b'\n', &mut batch)? == 0 {
+                    eof = true;
+                    break;
+                }
+            }
+            self.colorize_batch(&batch, out, piece_size)?;
+        }
+        Ok(())
+    }
+
+    fn colorize_batch(
+        &mut self,
+        batch: &[u8],
+        out: &mut impl Write,
+        piece_size: usize,
+    ) -> std::io::Result<()> {
+        // Split at line boundaries, and find the row each piece starts at
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        let mut y = self.y;
+        while start < batch.len() {
+            let end = (start + piece_size).min(batch.len());
+            let end = batch[end..]
+                .find_byte(b'\n')
+                .map_or(batch.len(), |i| end + i + 1);
+            let piece = &batch[start..end];
+            pieces.push((piece, y));
+            y += piece.iter().filter(|&&b| b == b'\n').count() as isize;
+            start = end;
+        }
+
+        if self.lut_size > 1 {
+            // build it once, instead of in every piece
+            self.lut();
+        }
+        // pieces are written in order as soon as they and the ones before
+        // them are done, the last one has the state to continue with
+        let this = &*self;
+        let (tx, rx) = mpsc::channel();
+        let last = thread::scope(|scope| {
+            scope.spawn(move || {
+                pieces
+                    .par_iter()
+                    .enumerate()
+                    .try_for_each_with(tx, |tx, (i, &(mut piece, y))| {
+                        let mut lol = this.clone();
+                        if i > 0 {
+                            lol.x = 0;
+                        }
+                        lol.y = y;
+                        let mut buf = Vec::with_capacity(piece.len() * 20);
+                        let result = lol.colorize_read(&mut piece, &mut buf).map(|()| (buf, lol));
+                        // fails when writing failed, then the other pieces aren't needed
+                        tx.send((i, result)).map_err(drop)
+                    })
+            });
+
+            let mut done = BTreeMap::new();
+            let mut next = 0;
+            let mut last = None;
+            for (i, result) in rx {
+                done.insert(i, result?);
+                while let Some((buf, lol)) = done.remove(&next) {
+                    out.write_all(&buf)?;
+                    last = Some(lol);
+                    next += 1;
+                }
+            }
+            std::io::Result::Ok(last)
+        })?;
+
+        if let Some(lol) = last {
+            *self = lol;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Continuity, Lolcrab};
+
+    #[test]
+    fn eq_sequential() {
+        let text = "Lolcrab\tis\r\nthe best\n\n\x1B[1m🦀\x1B[0m\n".repeat(50) + "no newline";
+
+        for (invert, compress) in [(false, false), (true, false), (false, true)] {
+            let mut lol1 = Lolcrab::with_seed(9);
+            lol1.set_invert(invert);
+            lol1.set_compress(compress);
+            let mut out1 = Vec::new();
+            lol1.colorize_read(&mut text.as_bytes(), &mut out1).unwrap();
+
+            let mut lol2 = Lolcrab::with_seed(9);
+            lol2.set_invert(invert);
+            lol2.set_compress(compress);
+            let mut out2 = Vec::new();
+            lol2.colorize_read_par_with(&mut text.as_bytes(), &mut out2, 100, 10)
+                .unwrap();
+
+            assert_eq!(out1, out2);
+            // the whole state continues the same
+            for lol in [&mut lol1, &mut lol2] {
+                lol.set_paragraph_continuity(Continuity::Randomize);
+                lol.colorize_read(&mut "a\n\nb\n".as_bytes(), &mut Vec::new())
+                    .unwrap();
+            }
+            assert_eq!(format!("{lol1:?}"), format!("{lol2:?}"));
+            let (mut out1, mut out2) = (Vec::new(), Vec::new());
+            lol1.colorize_str("\nLolcrab\n", &mut out1).unwrap();
+            lol2.colorize_str("\nLolcrab\n", &mut out2).unwrap();
+            assert_eq!(out1, out2);
+        }
+    }
+}
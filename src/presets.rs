@@ -0,0 +1,46 @@
+// Every gradient from `colorgrad::preset`, and our own. Included by the
+// library for the `Gradient` enum, and by the command line for the names.
+presets! {
+    Blues "blues" => colorgrad::preset::blues(),
+    BrBg "br-bg" => colorgrad::preset::br_bg(),
+    BuGn "bu-gn" => colorgrad::preset::bu_gn(),
+    BuPu "bu-pu" => colorgrad::preset::bu_pu(),
+    Cividis "cividis" => colorgrad::preset::cividis(),
+    Cool "cool" => colorgrad::preset::cool(),
+    Cubehelix "cubehelix" => colorgrad::preset::cubehelix_default(),
+    Fruits "fruits" => build_gradient(&[
+        "#00c21c", "#009dc9", "#ffd43e", "#ff2a70", "#b971ff", "#7ce300", "#feff62",
+    ]),
+    GnBu "gn-bu" => colorgrad::preset::gn_bu(),
+    Greens "greens" => colorgrad::preset::greens(),
+    Greys "greys" => colorgrad::preset::greys(),
+    Inferno "inferno" => colorgrad::preset::inferno(),
+    Lolcat "lolcat" => LolcatGradient {},
+    Magma "magma" => colorgrad::preset::magma(),
+    OrRd "or-rd" => colorgrad::preset::or_rd(),
+    Oranges "oranges" => colorgrad::preset::oranges(),
+    PiYg "pi-yg" => colorgrad::preset::pi_yg(),
+    Plasma "plasma" => colorgrad::preset::plasma(),
+    PrGn "pr-gn" => colorgrad::preset::pr_gn(),
+    PuBu "pu-bu" => colorgrad::preset::pu_bu(),
+    PuBuGn "pu-bu-gn" => colorgrad::preset::pu_bu_gn(),
+    PuOr "pu-or" => colorgrad::preset::pu_or(),
+    PuRd "pu-rd" => colorgrad::preset::pu_rd(),
+    Purples "purples" => colorgrad::preset::purples(),
+    Rainbow "rainbow" => colorgrad::preset::rainbow(),
+    RdBu "rd-bu" => colorgrad::preset::rd_bu(),
+    RdGy "rd-gy" => colorgrad::preset::rd_gy(),
+    RdPu "rd-pu" => colorgrad::preset::rd_pu(),
+    RdYlBu "rd-yl-bu" => colorgrad::preset::rd_yl_bu(),
+    RdYlGn "rd-yl-gn" => colorgrad::preset::rd_yl_gn(),
+    Reds "reds" => colorgrad::preset::reds(),
+    Sinebow "sinebow" => colorgrad::preset::sinebow(),
+    Spectral "spectral" => colorgrad::preset::spectral(),
+    Turbo "turbo" => colorgrad::preset::turbo(),
+    Viridis "viridis" => colorgrad::preset::viridis(),
+    Warm "warm" => colorgrad::preset::warm(),
+    YlGn "yl-gn" => colorgrad::preset::yl_gn(),
+    YlGnBu "yl-gn-bu" => colorgrad::preset::yl_gn_bu(),
+    YlOrBr "yl-or-br" => colorgrad::preset::yl_or_br(),
+    YlOrRd "yl-or-rd" => colorgrad::preset::yl_or_rd(),
+}
@@ -0,0 +1,109 @@
+use std::io::Write;
+
+use bstr::ByteSlice;
+
+use crate::Lolcrab;
+
+/// Colorizer state for input that arrives in arbitrary chunks.
+///
+/// Incomplete UTF-8 sequences and the last grapheme cluster (which may still
+/// be extended by the next chunk) are kept back until more input arrives.
+/// The state of escape sequences is carried across chunks. Colors are reset
+/// after every line, the same as colorizing line by line.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Stream {
+    pending: Vec<u8>,
+    escaping: bool,
+    dirty: bool,
+}
+
+impl Stream {
+    /// Colorize everything in `buf` that can't be changed by later input
+    pub(crate) fn feed(
+        &mut self,
+        lol: &mut Lolcrab,
+        buf: &[u8],
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        self.pending.extend_from_slice(buf);
+        let end = self.pending.len() - incomplete_utf8_len(&self.pending);
+        let end = match self.pending[..end].grapheme_indices().next_back() {
+            Some((start, _, g)) if !ends_grapheme(g) => start,
+            _ => end,
+        };
+        self.process(lol, end, out)
+    }
+
+    /// Colorize all complete graphemes and reset the colors
+    pub(crate) fn flush(&mut self, lol: &mut Lolcrab, out: &mut impl Write) -> std::io::Result<()> {
+        let end = self.pending.len() - incomplete_utf8_len(&self.pending);
+        self.process(lol, end, out)?;
+        self.close(lol, out)
+    }
+
+    /// Colorize all remaining input and reset the colors
+    pub(crate) fn finish(
+        &mut self,
+        lol: &mut Lolcrab,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        self.process(lol, self.pending.len(), out)?;
+        self.close(lol, out)
+    }
+
+    fn process(
+        &mut self,
+        lol: &mut Lolcrab,
+        end: usize,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        for g in self.pending[..end].graphemes() {
+            self.escaping = lol.colorize_grapheme(out, g, self.escaping)?;
+            if g == "\n" || g == "\r\n" {
+                lol.write_reset(out)?;
+                self.dirty = false;
+            } else {
+                self.dirty = true;
+            }
+        }
+        self.pending.drain(..end);
+        Ok(())
+    }
+
+    fn close(&mut self, lol: &mut Lolcrab, out: &mut impl Write) -> std::io::Result<()> {
+        // Don't break an escape sequence from the input
+        if self.dirty && !self.escaping {
+            lol.write_reset(out)?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+// Number of bytes at the end of `buf` that could be the start of a UTF-8
+// sequence which isn't complete yet
+fn incomplete_utf8_len(buf: &[u8]) -> usize {
+    for i in 1..=buf.len().min(3) {
+        let b = buf[buf.len() - i];
+        if b & 0xC0 != 0x80 {
+            let len = match b {
+                0xC2..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF4 => 4,
+                _ => return 0,
+            };
+            return if len > i { i } else { 0 };
+        }
+    }
+    0
+}
+
+// Nothing can be appended to a grapheme cluster after a control character,
+// except LF after CR
+fn ends_grapheme(g: &str) -> bool {
+    match g.as_bytes() {
+        [b'\r'] => false,
+        [c] => c.is_ascii_control(),
+        _ => g == "\r\n",
+    }
+}
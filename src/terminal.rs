@@ -0,0 +1,109 @@
+use std::io::{self, Write};
+use std::ops::{Deref, DerefMut};
+
+pub(crate) const HIDE_CURSOR: &[u8] = b"\x1B[?25l";
+
+// SGR reset and show the cursor
+pub(crate) const RESTORE: &[u8] = b"\x1B[0m\x1B[?25h";
+
+/// Reset all colors and show the cursor
+///
+/// # Errors
+///
+/// Will return `Err` if `out` causes I/O errors
+pub fn restore_terminal(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(RESTORE)?;
+    out.flush()
+}
+
+/// Restores the terminal when dropped, also when unwinding from a panic.
+///
+/// Writes through to the wrapped writer. On drop it resets all colors and shows
+/// the cursor, so an interrupted animation doesn't leave the terminal colored
+/// and without a cursor.
+///
+/// # Example
+///
+/// ```
+/// use lolcrab::{Lolcrab, TerminalGuard};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut lol = Lolcrab::new(None, None);
+/// let mut stdout = std::io::stdout();
+/// let mut out = TerminalGuard::new(&mut stdout);
+/// out.hide_cursor()?;
+/// lol.colorize_str("Lolcrab is the best", &mut *out)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TerminalGuard<'a, W: Write> {
+    out: &'a mut W,
+}
+
+impl<'a, W: Write> TerminalGuard<'a, W> {
+    pub fn new(out: &'a mut W) -> Self {
+        Self { out }
+    }
+
+    /// Hide the cursor until the guard is dropped
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the writer causes I/O errors
+    pub fn hide_cursor(&mut self) -> io::Result<()> {
+        self.out.write_all(HIDE_CURSOR)
+    }
+}
+
+impl<W: Write> Deref for TerminalGuard<'_, W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        self.out
+    }
+}
+
+impl<W: Write> DerefMut for TerminalGuard<'_, W> {
+    fn deref_mut(&mut self) -> &mut W {
+        self.out
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<'_, W> {
+    fn drop(&mut self) {
+        // there is nowhere to report errors to
+        let _ = restore_terminal(self.out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::{TerminalGuard, HIDE_CURSOR, RESTORE};
+    use crate::Lolcrab;
+
+    #[test]
+    fn restore_on_drop() {
+        let mut out = Vec::new();
+        {
+            let mut guard = TerminalGuard::new(&mut out);
+            guard.hide_cursor().unwrap();
+            guard.extend_from_slice(b"\x1B[41m");
+        }
+        assert_eq!(out, [HIDE_CURSOR, b"\x1B[41m", RESTORE].concat());
+    }
+
+    #[test]
+    fn restore_on_panic() {
+        let mut out = Vec::new();
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let mut guard = TerminalGuard::new(&mut out);
+            let mut lol = Lolcrab::new(None, None);
+            lol.colorize_str("Lolcrab", &mut *guard).unwrap();
+            panic!("interrupted");
+        }));
+        assert!(result.is_err());
+        assert!(out.ends_with(RESTORE));
+    }
+}
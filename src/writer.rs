@@ -0,0 +1,127 @@
+use std::io::{self, Write};
+
+use crate::stream::Stream;
+use crate::Lolcrab;
+
+/// Colorize everything written through it.
+///
+/// Partial UTF-8 sequences, grapheme clusters and escape sequences are
+/// buffered across `write` calls. The colors are reset on `flush` and
+/// when the writer is dropped.
+///
+/// # Example
+///
+/// ```
+/// # use std::error::Error;
+/// use std::io::Write;
+/// use lolcrab::{Lolcrab, LolcrabWriter};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let stdout = std::io::stdout();
+/// let mut out = LolcrabWriter::new(Lolcrab::new(None, None), stdout.lock());
+///
+/// writeln!(out, "Lolcrab is the best")?;
+/// out.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct LolcrabWriter<W: Write> {
+    lol: Lolcrab,
+    inner: W,
+    stream: Stream,
+}
+
+impl<W: Write> LolcrabWriter<W> {
+    #[must_use]
+    pub fn new(lol: Lolcrab, inner: W) -> Self {
+        Self {
+            lol,
+            inner,
+            stream: Stream::default(),
+        }
+    }
+
+    /// Gets a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Gets a mutable reference to the `Lolcrab` used to colorize
+    pub fn lolcrab(&mut self) -> &mut Lolcrab {
+        &mut self.lol
+    }
+}
+
+impl<W: Write> Write for LolcrabWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.feed(&mut self.lol, buf, &mut self.inner)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush(&mut self.lol, &mut self.inner)?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for LolcrabWriter<W> {
+    fn drop(&mut self) {
+        // Errors can't be reported from drop
+        let _ = self.stream.finish(&mut self.lol, &mut self.inner);
+        let _ = self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::LolcrabWriter;
+    use crate::tests::new_lol;
+
+    fn write_chunked(text: &[u8], size: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut w = LolcrabWriter::new(new_lol(11), &mut out);
+        for chunk in text.chunks(size) {
+            w.write_all(chunk).unwrap();
+        }
+        drop(w);
+        out
+    }
+
+    #[test]
+    fn eq_colorize_read() {
+        let text = "Lolcrab\r\n\x1B[1mbold\x1B[0m\tthe best\ne\u{301}🦀 한국어\n";
+
+        let mut expected = Vec::new();
+        new_lol(11)
+            .colorize_read(&mut text.as_bytes(), &mut expected)
+            .unwrap();
+
+        for size in 1..=text.len() {
+            assert_eq!(write_chunked(text.as_bytes(), size), expected);
+        }
+    }
+
+    #[test]
+    fn reset_on_flush() {
+        let crab = "🦀".as_bytes();
+        let mut out = Vec::new();
+        let mut w = LolcrabWriter::new(new_lol(0), &mut out);
+
+        // the incomplete UTF-8 sequence is kept back
+        w.write_all(b"ab").unwrap();
+        w.write_all(&crab[..2]).unwrap();
+        w.flush().unwrap();
+        assert!(w.get_ref().ends_with(b"b\x1B[39m"));
+
+        w.write_all(&crab[2..]).unwrap();
+        drop(w);
+        assert!(out.ends_with("🦀\x1B[39m".as_bytes()));
+    }
+}